@@ -3,17 +3,12 @@ use gtk4::{
     Fixed, MessageDialog , gdk::Display , CssProvider, glib, ResponseType, FileChooserDialog, FileChooserAction, gdk, ScrolledWindow
 };
 use std::{cell::RefCell, fs, path::PathBuf, process::Command, rc::Rc};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::hash::{DefaultHasher, Hash, Hasher};
 use std::env;
-use std::io::{Write, BufReader, BufRead};
-use std::fs::OpenOptions;
+use std::io::Write;
 use std::path::Path;
-use networkmanager::{NetworkManager, devices::Device};
-use networkmanager::devices::Wireless;
 use dbus::blocking::Connection as DbusConnection;
-use vte4::Terminal;
-use vte4::TerminalExtManual;
-use vte4::PtyFlags;
 
 struct MonitorInfo {
     name: String,
@@ -25,6 +20,11 @@ struct MonitorInfo {
 
 const SCALE: f64 = 0.1;
 
+thread_local! {
+    // pending auto-hide of the footer notification, replaced by every new notification
+    static NOTIF_HIDE: RefCell<Option<glib::SourceId>> = const { RefCell::new(None) };
+}
+
 fn add_class_recursive (widget: &gtk4::Widget, class_name: &str) {
     widget.add_css_class(class_name);
 
@@ -332,6 +332,10 @@ fn setup_sound_switch(switch: &Switch) {
 }
 
 fn show_notification(notif_box: &GtkBox, text: &str) {
+    show_notification_for(notif_box, text, None, 4);
+}
+
+fn show_notification_for(notif_box: &GtkBox, text: &str, action: Option<&Button>, secs: u64) {
     let mut child = notif_box.first_child();
     while let Some(c) = child {
         child = c.next_sibling();  // get next before removing
@@ -341,192 +345,6646 @@ fn show_notification(notif_box: &GtkBox, text: &str) {
     let notif_label = Label::new(Some(text));
 
     notif_box.append(&notif_label);
+    if let Some(action) = action {
+        notif_box.append(action);
+    }
     notif_box.set_widget_name("notif_box");
     notif_box.show();
 
     let notif_box_clone = notif_box.clone();
-    glib::timeout_add_local(std::time::Duration::from_secs(4), move || {
+    let source = glib::timeout_add_local(std::time::Duration::from_secs(secs), move || {
         notif_box_clone.hide();
+        NOTIF_HIDE.with(|pending| pending.borrow_mut().take());
         glib::ControlFlow::Break
     });
+    NOTIF_HIDE.with(|pending| {
+        if let Some(previous) = pending.borrow_mut().replace(source) {
+            previous.remove();
+        }
+    });
+}
+
+/// Shows `text` with an Undo button. `undo` runs if it is clicked in time, otherwise `commit` runs.
+fn show_undo_notification(notif_box: &GtkBox, text: &str, undo: impl FnOnce() + 'static, commit: impl FnOnce() + 'static) {
+    const UNDO_SECS: u64 = 8;
+    let pending = Rc::new(RefCell::new(Some((undo, commit))));
+
+    let undo_button = Button::with_label("Undo");
+    let pending_clone = pending.clone();
+    undo_button.connect_clicked(move |btn| {
+        if let Some((undo, _)) = pending_clone.borrow_mut().take() {
+            undo();
+        }
+        btn.set_sensitive(false);
+    });
+    show_notification_for(notif_box, text, Some(&undo_button), UNDO_SECS);
+
+    glib::timeout_add_local_once(std::time::Duration::from_secs(UNDO_SECS), move || {
+        if let Some((_, commit)) = pending.borrow_mut().take() {
+            commit();
+        }
+    });
+}
+
+// Wallpaper import ----------------------------------------------------------------------------------------------------------------------------------- //
+
+/// Only narrows down which files to compare; equal content is confirmed byte for byte.
+fn file_content_hash(path: &Path) -> Option<u64> {
+    let bytes = fs::read(path).ok()?;
+    let mut hasher = DefaultHasher::new();
+    bytes.hash(&mut hasher);
+    Some(hasher.finish())
+}
+
+type KnownWallpapers = std::sync::Arc<std::sync::Mutex<HashMap<u64, Vec<PathBuf>>>>;
+
+fn known_wallpaper_hashes(dir: &Path) -> HashMap<u64, Vec<PathBuf>> {
+    let mut known: HashMap<u64, Vec<PathBuf>> = HashMap::new();
+    for entry in fs::read_dir(dir).into_iter().flatten().flatten() {
+        let path = entry.path();
+        if let Some(hash) = path.is_file().then(|| file_content_hash(&path)).flatten() {
+            known.entry(hash).or_default().push(path);
+        }
+    }
+    known
+}
+
+fn is_duplicate_wallpaper(path: &Path, same_hash: &[PathBuf]) -> bool {
+    let Ok(bytes) = fs::read(path) else {
+        return false;
+    };
+    same_hash.iter().any(|other| fs::read(other).is_ok_and(|other| other == bytes))
+}
+
+enum ImportOutcome {
+    Added,
+    Duplicate,
+    Failed(String),
 }
 
-fn is_image_dark(image_path: &str) -> bool {
-    let pixbuf = match gtk4::gdk::gdk_pixbuf::Pixbuf::from_file(image_path) {
-        Ok(p) => p,
-        Err(_) => return true, 
+/// Runs on a worker thread: decoding, hashing and cynagectl can all take a while.
+fn import_wallpaper_file(path: &Path, known: &KnownWallpapers) -> ImportOutcome {
+    let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+    if !is_decodable_image(path) {
+        return ImportOutcome::Failed(format!("{} (not an image)", name));
+    }
+    let Some(hash) = file_content_hash(path) else {
+        return ImportOutcome::Failed(format!("{} (unreadable)", name));
     };
+    let same_hash = known.lock().map(|k| k.get(&hash).cloned().unwrap_or_default()).unwrap_or_default();
+    if is_duplicate_wallpaper(path, &same_hash) {
+        return ImportOutcome::Duplicate;
+    }
+
+    match Command::new("cynagectl").arg("-w").arg("add").arg(path).status() {
+        Ok(status) if status.success() => {
+            if let Ok(mut known) = known.lock() {
+                known.entry(hash).or_default().push(path.to_path_buf());
+            }
+            ImportOutcome::Added
+        }
+        _ => ImportOutcome::Failed(name),
+    }
+}
 
-    let width = pixbuf.width();
-    let height = pixbuf.height();
-     let pixels = unsafe { pixbuf.pixels() };
-    let rowstride = pixbuf.rowstride();
-    let n_channels = pixbuf.n_channels();
+fn is_decodable_image(path: &Path) -> bool {
+    if is_video_wallpaper(path) {
+        return video_frame(path).is_some();
+    }
+    gtk4::gdk_pixbuf::Pixbuf::from_file_at_scale(path, 64, 64, true).is_ok()
+}
 
-    let mut total_luminance = 0.0;
-    let mut count = 0;
+fn collect_import_candidates(paths: &[PathBuf], out: &mut Vec<PathBuf>) {
+    for path in paths {
+        if path.is_dir() {
+            if let Ok(entries) = fs::read_dir(path) {
+                let mut children: Vec<PathBuf> = entries.flatten().map(|e| e.path()).collect();
+                children.sort();
+                collect_import_candidates(&children, out);
+            }
+        } else if path.is_file() {
+            out.push(path.clone());
+        }
+    }
+}
 
-    let sample_step = 10;
+fn import_wallpapers(paths: Vec<PathBuf>, notif_box: &GtkBox, on_done: impl Fn() + 'static) {
+    let mut candidates = Vec::new();
+    collect_import_candidates(&paths, &mut candidates);
+    if candidates.is_empty() {
+        show_notification(notif_box, "nothing to import");
+        return;
+    }
 
-    for y in (0..height).step_by(sample_step) {
-        for x in (0..width).step_by(sample_step) {
-            let offset = (y * rowstride + x * n_channels) as usize;
-            let r = pixels[offset] as f64;
-            let g = pixels[offset + 1] as f64;
-            let b = pixels[offset + 2] as f64;
+    let home_dir = env::var("HOME").unwrap_or_default();
+    let wallpaper_dir = PathBuf::from(format!("{}/.config/swww/cynage", home_dir));
+    let total = candidates.len();
+    let notif_box = notif_box.clone();
+
+    // files are handled one at a time on a worker thread so the footer can show progress
+    glib::MainContext::default().spawn_local(async move {
+        show_notification(&notif_box, "checking the wallpapers already imported…");
+        let known = gtk4::gio::spawn_blocking(move || known_wallpaper_hashes(&wallpaper_dir)).await.unwrap_or_default();
+        let known: KnownWallpapers = std::sync::Arc::new(std::sync::Mutex::new(known));
+        let (mut added, mut duplicates) = (0, 0);
+        let mut failed: Vec<String> = Vec::new();
+
+        for (i, path) in candidates.into_iter().enumerate() {
+            let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+            show_notification(&notif_box, &format!("importing {}/{}: {}", i + 1, total, name));
+            let known = known.clone();
+            match gtk4::gio::spawn_blocking(move || import_wallpaper_file(&path, &known)).await {
+                Ok(ImportOutcome::Added) => added += 1,
+                Ok(ImportOutcome::Duplicate) => duplicates += 1,
+                Ok(ImportOutcome::Failed(reason)) => failed.push(reason),
+                Err(_) => failed.push(name),
+            }
+        }
 
-            let luminance = 0.2126 * r + 0.7152 * g + 0.0722 * b;
-            total_luminance += luminance;
-            count += 1;
+        let mut summary = format!("imported {} of {} wallpapers", added, total);
+        if duplicates > 0 {
+            summary.push_str(&format!(", {} duplicates skipped", duplicates));
         }
+        if !failed.is_empty() {
+            summary.push_str(&format!(", failed: {}", failed.join(", ")));
+        }
+        show_notification(&notif_box, &summary);
+        on_done();
+    });
+}
+
+// Auto theme ---------------------------------------------------------------------------------------------------------------------------------------- //
+// Wallpapers are classified by their average lightness (CIE L*, 0-100) computed in linear light.
+// Regions are fractions of the image; the first region containing a sample decides its weight,
+// everything else weighs 1.0. Settings live in ~/.config/swww/autotheme.ctl:
+//     threshold|50
+//     region|0|0|1|0.06|3        (x|y|w|h|weight)
+//     ignore|<wallpaper file name>
+
+struct WeightedRegion {
+    x: f64,
+    y: f64,
+    w: f64,
+    h: f64,
+    weight: f64,
+}
+
+struct AutoThemeConfig {
+    threshold: f64,
+    regions: Vec<WeightedRegion>,
+    ignored: Vec<String>,
+}
+
+impl AutoThemeConfig {
+    fn default_regions() -> Vec<WeightedRegion> {
+        vec![
+            // top bar
+            WeightedRegion { x: 0.0, y: 0.0, w: 1.0, h: 0.06, weight: 3.0 },
+            // where windows usually sit
+            WeightedRegion { x: 0.1, y: 0.1, w: 0.8, h: 0.8, weight: 1.5 },
+        ]
+    }
+
+    fn is_ignored(&self, wallpaper: &str) -> bool {
+        self.ignored.iter().any(|name| name == wallpaper)
     }
 
-    let avg_luminance = total_luminance / count as f64;
+    fn set_ignored(&mut self, wallpaper: &str, ignored: bool) {
+        self.ignored.retain(|name| name != wallpaper);
+        if ignored {
+            self.ignored.push(wallpaper.to_string());
+        }
+    }
+}
 
-    println!("Average luminance: {}", avg_luminance);
-    avg_luminance <= 128.0  // true = dark, false = light
+fn auto_theme_config_path() -> PathBuf {
+    let home_dir = env::var("HOME").unwrap_or_default();
+    PathBuf::from(format!("{}/.config/swww/autotheme.ctl", home_dir))
 }
 
-fn extract_current_selection(config_path: &str) -> Option<String> {
-    if let Ok(file) = fs::File::open(config_path) {
-        for line in BufReader::new(file).lines().flatten() {
-            if line.starts_with("exec-once") {
-                // Extract filename
-                if let Some(start) = line.find("/sound/startup/") {
-                    let part = &line[start + "/sound/startup/".len()..];
-                    if let Some(end) = part.find('"') {
-                        return Some(part[..end].to_string());
-                    }
-                    return Some(part.to_string());
+fn parse_auto_theme_config(content: &str) -> AutoThemeConfig {
+    let mut threshold = 50.0;
+    let mut regions = Vec::new();
+    let mut ignored = Vec::new();
+
+    for line in content.lines() {
+        let parts: Vec<&str> = line.trim().split('|').collect();
+        match parts.as_slice() {
+            ["threshold", value] => {
+                if let Ok(value) = value.trim().parse::<f64>() {
+                    threshold = value.clamp(0.0, 100.0);
                 }
             }
+            ["region", x, y, w, h, weight] => {
+                let nums: Vec<f64> = [x, y, w, h, weight]
+                    .iter()
+                    .filter_map(|v| v.trim().parse::<f64>().ok())
+                    .collect();
+                if let [x, y, w, h, weight] = nums[..] {
+                    regions.push(WeightedRegion { x, y, w, h, weight: weight.max(0.0) });
+                }
+            }
+            ["ignore", name] if !name.is_empty() => ignored.push(name.to_string()),
+            _ => {}
         }
     }
-    None
+
+    if regions.is_empty() {
+        regions = AutoThemeConfig::default_regions();
+    }
+
+    AutoThemeConfig { threshold, regions, ignored }
+}
+
+fn load_auto_theme_config() -> AutoThemeConfig {
+    let content = fs::read_to_string(auto_theme_config_path()).unwrap_or_default();
+    parse_auto_theme_config(&content)
+}
+
+fn save_auto_theme_config(config: &AutoThemeConfig) {
+    let mut content = format!("threshold|{}\n", config.threshold);
+    for r in &config.regions {
+        content.push_str(&format!("region|{}|{}|{}|{}|{}\n", r.x, r.y, r.w, r.h, r.weight));
+    }
+    for name in &config.ignored {
+        content.push_str(&format!("ignore|{}\n", name));
+    }
+    if let Err(e) = fs::write(auto_theme_config_path(), content) {
+        eprintln!("Failed to save auto theme settings: {}", e);
+    }
+}
+
+fn srgb_to_linear(c: u8) -> f64 {
+    let c = c as f64 / 255.0;
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_lightness(y: f64) -> f64 {
+    if y > 216.0 / 24389.0 {
+        116.0 * y.cbrt() - 16.0
+    } else {
+        y * 24389.0 / 27.0
+    }
+}
+
+fn region_weight(regions: &[WeightedRegion], fx: f64, fy: f64) -> f64 {
+    regions
+        .iter()
+        .find(|r| fx >= r.x && fx < r.x + r.w && fy >= r.y && fy < r.y + r.h)
+        .map(|r| r.weight)
+        .unwrap_or(1.0)
+}
+
+fn pixbuf_lightness(pixbuf: &gtk4::gdk_pixbuf::Pixbuf, regions: &[WeightedRegion]) -> Option<f64> {
+    let width = pixbuf.width().max(0) as usize;
+    let height = pixbuf.height().max(0) as usize;
+    let rowstride = pixbuf.rowstride().max(0) as usize;
+    let n_channels = pixbuf.n_channels().max(0) as usize;
+    let has_alpha = pixbuf.has_alpha();
+    if width == 0 || height == 0 || n_channels == 0 {
+        return None;
+    }
+
+    let bytes = pixbuf.read_pixel_bytes();
+    let pixels: &[u8] = &bytes;
+
+    let lut: Vec<f64> = (0..=255u8).map(srgb_to_linear).collect();
+    let sample_step = (width.max(height) / 256).max(1);
+
+    let mut total = 0.0;
+    let mut total_weight = 0.0;
+
+    for y in (0..height).step_by(sample_step) {
+        for x in (0..width).step_by(sample_step) {
+            let offset = y * rowstride + x * n_channels;
+            let Some(px) = pixels.get(offset..offset + n_channels) else {
+                continue;
+            };
+
+            let luminance = if n_channels >= 3 {
+                0.2126 * lut[px[0] as usize] + 0.7152 * lut[px[1] as usize] + 0.0722 * lut[px[2] as usize]
+            } else {
+                lut[px[0] as usize]
+            };
+            // transparent areas show swww's black backdrop
+            let alpha = if has_alpha && n_channels > 1 { px[n_channels - 1] as f64 / 255.0 } else { 1.0 };
+
+            let weight = region_weight(regions, x as f64 / width as f64, y as f64 / height as f64);
+            total += luminance * alpha * weight;
+            total_weight += weight;
+        }
+    }
+
+    if total_weight <= 0.0 {
+        return None;
+    }
+    Some(linear_to_lightness(total / total_weight))
+}
+
+#[derive(Clone)]
+struct WallState {
+    current_pic: Rc<RefCell<gtk4::Picture>>,
+    // "picture" or "placeholder" when nothing is set yet
+    current_preview: Stack,
+    current_name: Rc<RefCell<Option<String>>>,
+    auto_theme: Rc<RefCell<AutoThemeConfig>>,
+    ignore_check: gtk4::CheckButton,
+    select_toggle: gtk4::ToggleButton,
+    selected: Rc<RefCell<Vec<PathBuf>>>,
+    meta: Rc<RefCell<HashMap<String, WallMetaEntry>>>,
+    // wallpapers whose brightness and colour were already worked out (or tried) this session
+    stats_tried: Rc<RefCell<HashSet<String>>>,
+    scroller: ScrolledWindow,
+    strip: GtkBox,
+    gallery: gtk4::FlowBox,
+    search: gtk4::SearchEntry,
+    sort: gtk4::DropDown,
+    favourites_toggle: gtk4::ToggleButton,
+    // file name and thumbnail button of every wallpaper in the grid, for filtering in place
+    wall_buttons: Rc<RefCell<Vec<(String, Button)>>>,
+    gallery_toggle: gtk4::ToggleButton,
+    video_output: gtk4::DropDown,
+}
+
+/// Some(true) = dark, Some(false) = light, None = leave the theme alone
+/// (undecodable, or the user opted this wallpaper out of auto-theme).
+/// `image_path` is what is on screen, a cropped copy included; the opt-out is looked up by `name`.
+fn is_image_dark(name: &str, image_path: &Path, config: &AutoThemeConfig) -> Option<bool> {
+    if config.is_ignored(name) {
+        return None;
+    }
+
+    let sample = wallpaper_sample(image_path)?;
+    let pixbuf = gtk4::gdk_pixbuf::Pixbuf::from_file_at_scale(sample, 512, 512, true).ok()?;
+    pixbuf_is_dark(&pixbuf, config)
+}
+
+fn pixbuf_is_dark(pixbuf: &gtk4::gdk_pixbuf::Pixbuf, config: &AutoThemeConfig) -> Option<bool> {
+    Some(pixbuf_lightness(pixbuf, &config.regions)? <= config.threshold)
+}
+
+const SOUND_PLAYERS: [&str; 3] = ["mpv", "pw-play", "paplay"];
+
+#[derive(Clone, Debug, PartialEq)]
+struct StartupSound {
+    file: Option<String>,
+    player: String,
+    // percent, 0..=100
+    volume: u32,
+}
+
+impl Default for StartupSound {
+    fn default() -> Self {
+        StartupSound {
+            file: None,
+            player: available_sound_players().first().unwrap_or(&"mpv").to_string(),
+            volume: 100,
+        }
+    }
+}
+
+fn find_in_path(program: &str) -> bool {
+    env::var("PATH")
+        .unwrap_or_default()
+        .split(':')
+        .any(|dir| Path::new(dir).join(program).is_file())
+}
+
+fn available_sound_players() -> Vec<&'static str> {
+    SOUND_PLAYERS.into_iter().filter(|player| find_in_path(player)).collect()
+}
+
+/// The exec-once line playing `sound`, None when the startup sound is off.
+fn startup_sound_line(sound: &StartupSound) -> Option<String> {
+    let file = sound.file.as_ref()?;
+    let path = format!("\"$HOME/.config/hypr/sound/startup/{}\"", file);
+    let command = match sound.player.as_str() {
+        "pw-play" => format!("pw-play --volume={:.2} {}", sound.volume as f64 / 100.0, path),
+        "paplay" => format!("paplay --volume={} {}", sound.volume * 65536 / 100, path),
+        _ => format!("mpv --no-video --volume={} {}", sound.volume, path),
+    };
+    Some(format!("exec-once = {}", command))
+}
+
+fn parse_startup_sound_line(line: &str) -> Option<StartupSound> {
+    let command = line.trim().strip_prefix("exec-once")?.trim_start().strip_prefix('=')?.trim();
+    let start = command.find("/sound/startup/")?;
+    let part = &command[start + "/sound/startup/".len()..];
+    let file = part.split('"').next().unwrap_or(part).to_string();
+
+    let player = command.split_whitespace().next().unwrap_or("mpv").rsplit('/').next().unwrap_or("mpv").to_string();
+    let raw_volume = command
+        .split_whitespace()
+        .find_map(|arg| arg.strip_prefix("--volume="))
+        .and_then(|v| v.parse::<f64>().ok());
+    let volume = match (player.as_str(), raw_volume) {
+        (_, None) => 100.0,
+        ("pw-play", Some(v)) => v * 100.0,
+        ("paplay", Some(v)) => v * 100.0 / 65536.0,
+        (_, Some(v)) => v,
+    };
+    Some(StartupSound {
+        file: Some(file),
+        player,
+        volume: volume.round().clamp(0.0, 100.0) as u32,
+    })
+}
+
+/// The tagged line wins, even while it is commented out on the Autostart page; before calibrate
+/// tagged it, the first sound line is taken.
+fn extract_current_selection(config_path: &str) -> Option<StartupSound> {
+    let content = fs::read_to_string(config_path).ok()?;
+    let tagged = content
+        .lines()
+        .filter(|line| line.trim_end().ends_with(STARTUP_SOUND_MARKER))
+        .find_map(|line| parse_startup_sound_line(split_comment_prefix(line).1));
+    tagged.or_else(|| content.lines().find_map(parse_startup_sound_line))
+}
+
+const STARTUP_SOUND_MARKER: &str = "# calibrate:startup-sound";
+
+/// Splits a config line into its indentation and comment characters (including the Autostart
+/// page's disabled marker), and the rest.
+fn split_comment_prefix(line: &str) -> (&str, &str) {
+    let trimmed = line.trim_start();
+    let trimmed = trimmed.strip_prefix(AUTOSTART_DISABLED_MARKER).unwrap_or(trimmed);
+    let body = trimmed.trim_start_matches('#').trim_start();
+    (&line[..line.len() - body.len()], body)
+}
+
+/// Replaces the line tagged with `marker` by `line` (removing it for None) and leaves every
+/// other line alone. Without a tagged line the first line `is_legacy` accepts is taken over,
+/// otherwise the new line is appended. A tagged line that was commented out stays commented out.
+fn set_managed_line(content: &str, marker: &str, line: Option<&str>, is_legacy: impl Fn(&str) -> bool) -> String {
+    let tagged = line.map(|l| format!("{} {}", l, marker));
+    let has_marker = content.lines().any(|l| l.trim_end().ends_with(marker));
+    let mut replaced = false;
+    let mut out: Vec<String> = Vec::new();
+
+    for existing in content.lines() {
+        let managed = if has_marker { existing.trim_end().ends_with(marker) } else { !replaced && is_legacy(existing) };
+        if !managed {
+            out.push(existing.to_string());
+            continue;
+        }
+        // drops duplicates of the managed line as well
+        if !replaced {
+            let (prefix, _) = split_comment_prefix(existing);
+            out.extend(tagged.as_ref().map(|t| format!("{}{}", prefix, t)));
+            replaced = true;
+        }
+    }
+    let appended = !replaced && tagged.is_some();
+    if appended {
+        out.extend(tagged);
+    }
+
+    let mut result = out.join("\n");
+    if !result.is_empty() && (content.ends_with('\n') || appended) {
+        result.push('\n');
+    }
+    result
+}
+
+fn write_startup_sound(config_path: &str, sound: &StartupSound) -> std::io::Result<()> {
+    let content = match fs::read_to_string(config_path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e),
+    };
+    let line = startup_sound_line(sound);
+    let updated = set_managed_line(&content, STARTUP_SOUND_MARKER, line.as_deref(), |l| parse_startup_sound_line(l).is_some());
+    if updated != content {
+        fs::write(config_path, updated)?;
+    }
+    Ok(())
+}
+
+// the sound being previewed and its play button, only one plays at a time
+type SoundPreview = Rc<RefCell<Option<(gtk4::MediaFile, Button)>>>;
+
+fn format_duration(micros: i64) -> String {
+    let secs = (micros / 1_000_000).max(0);
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
+
+fn stop_sound_preview(preview: &SoundPreview) {
+    if let Some((media, play_btn)) = preview.borrow_mut().take() {
+        media.pause();
+        media.seek(0);
+        play_btn.set_label("▶");
+    }
+}
+
+/// Wraps a sound's select button with a play/stop button and its duration.
+fn sound_tile(path: &Path, select_btn: &Button, preview: &SoundPreview) -> GtkBox {
+    let tile = GtkBox::new(Orientation::Vertical, 2);
+    let controls = GtkBox::new(Orientation::Horizontal, 5);
+    controls.set_halign(gtk4::Align::Center);
+    let play_btn = Button::with_label("▶");
+    play_btn.set_tooltip_text(Some("Preview"));
+    let duration_label = Label::new(Some("-:--"));
+    controls.append(&play_btn);
+    controls.append(&duration_label);
+    tile.append(select_btn);
+    tile.append(&controls);
+
+    let media = gtk4::MediaFile::for_filename(path);
+    let duration_clone = duration_label.clone();
+    media.connect_duration_notify(move |media| {
+        duration_clone.set_text(&format_duration(media.duration()));
+    });
+    let play_clone = play_btn.clone();
+    media.connect_error_notify(move |media| {
+        if media.error().is_some() {
+            play_clone.set_sensitive(false);
+            play_clone.set_tooltip_text(Some("This file can not be played"));
+        }
+    });
+    let preview_clone = preview.clone();
+    media.connect_ended_notify(move |media| {
+        if media.is_ended() {
+            stop_sound_preview(&preview_clone);
+        }
+    });
+
+    let preview_clone = preview.clone();
+    play_btn.connect_clicked(move |play_btn| {
+        let was_playing = preview_clone
+            .borrow()
+            .as_ref()
+            .is_some_and(|(current, _)| current == &media);
+        stop_sound_preview(&preview_clone);
+        if !was_playing {
+            media.play();
+            play_btn.set_label("■");
+            *preview_clone.borrow_mut() = Some((media.clone(), play_btn.clone()));
+        }
+    });
+
+    tile
+}
+
+#[derive(Clone)]
+struct StartupSoundPage {
+    config_path: String,
+    current: Rc<RefCell<StartupSound>>,
+    selected_button: Rc<RefCell<Option<Button>>>,
+    preview: SoundPreview,
+    button_box: GtkBox,
+    notif_box: GtkBox,
+}
+
+impl StartupSoundPage {
+    fn save(&self) {
+        if let Err(e) = write_startup_sound(&self.config_path, &self.current.borrow()) {
+            show_notification(&self.notif_box, &format!("could not write startup.conf: {}", e));
+        }
+    }
+}
+
+/// A selectable sound button in `button_box`: a tile with preview controls for `path`, or a
+/// plain "None" choice when `path` is None.
+fn add_sound_button(
+    button_box: &GtkBox,
+    label: &str,
+    path: Option<&Path>,
+    selected: bool,
+    selected_button: &Rc<RefCell<Option<Button>>>,
+    preview: &SoundPreview,
+    on_select: impl Fn() + 'static,
+) -> Button {
+    let btn = Button::with_label(label);
+    btn.set_css_classes(&["sound_btn"]);
+    btn.set_size_request(100, 100);
+    btn.set_valign(gtk4::Align::Center);
+    btn.set_halign(gtk4::Align::Center);
+
+    // Initial highlight if currently selected
+    if selected {
+        btn.add_css_class("sound_btn_selected");
+        *selected_button.borrow_mut() = Some(btn.clone());
+    }
+
+    let selected_button_clone = selected_button.clone();
+    btn.connect_clicked(move |btn_ref| {
+        // Deselect previous button
+        if let Some(prev_btn) = selected_button_clone.borrow_mut().take() {
+            prev_btn.remove_css_class("sound_btn_selected");
+        }
+
+        // Select current
+        btn_ref.add_css_class("sound_btn_selected");
+        *selected_button_clone.borrow_mut() = Some(btn_ref.clone());
+        on_select();
+    });
+
+    match path {
+        Some(path) => button_box.append(&sound_tile(path, &btn, preview)),
+        None => button_box.append(&btn),
+    }
+    btn
+}
+
+fn add_startup_sound_button(page: &StartupSoundPage, path: Option<&Path>) {
+    let filename = path.and_then(|p| p.file_name()).map(|n| n.to_string_lossy().to_string());
+    let selected = page.current.borrow().file == filename;
+    let label = filename.clone().unwrap_or_else(|| "No startup sound".to_string());
+    let page_clone = page.clone();
+    add_sound_button(
+        &page.button_box,
+        &label,
+        path,
+        selected,
+        &page.selected_button,
+        &page.preview,
+        move || {
+            page_clone.current.borrow_mut().file = filename.clone();
+            page_clone.save();
+            show_notification(&page_clone.notif_box, "Startup Sound Modified");
+        },
+    );
+}
+
+// Event sounds ------------------------------------------------------------------------------------------------------------------------------------- //
+// Assignments live in ~/.config/hypr/sound/events.ctl as `event|/path/to/sound`. The hook script
+// plays one event when called with its name, or with --watch plays login and then follows usb
+// devices, the battery, new screenshots and the default sink volume; a user service runs the
+// watcher for the session.
+
+// (id, label, freedesktop sound name)
+const SOUND_EVENTS: [(&str, &str, &str); 7] = [
+    ("login", "Login", "desktop-login"),
+    ("logout", "Logout / shutdown", "desktop-logout"),
+    ("device-added", "USB device added", "device-added"),
+    ("device-removed", "USB device removed", "device-removed"),
+    ("battery-low", "Low battery", "battery-low"),
+    ("screenshot", "Screenshot", "screen-capture"),
+    ("volume", "Volume change", "audio-volume-change"),
+];
+
+const EVENT_SOUND_SCRIPT: &str = r#"#!/bin/sh
+# Written by calibrate, changes are overwritten.
+#   eventsound.sh <event>   play the sound assigned to <event>
+#   eventsound.sh --watch   play login, then follow usb devices, battery, screenshots and volume
+CONF="$HOME/.config/hypr/sound/events.ctl"
+
+play() {
+    file=$(grep "^$1|" "$CONF" 2>/dev/null | head -n1 | cut -d'|' -f2-)
+    [ -n "$file" ] && [ -f "$file" ] || return 0
+    if command -v pw-play >/dev/null; then pw-play "$file"
+    elif command -v paplay >/dev/null; then paplay "$file"
+    elif command -v mpv >/dev/null; then mpv --no-video --really-quiet "$file"
+    fi
+}
+
+if [ "$1" != "--watch" ]; then
+    play "$1"
+    exit 0
+fi
+
+# the trap is cleared first, kill 0 signals this script too
+trap 'trap - TERM INT; play logout; kill 0' TERM INT
+play login &
+
+udevadm monitor --udev --subsystem-match=usb/usb_device 2>/dev/null | while read -r _ _ action _; do
+    case "$action" in
+        add) play device-added & ;;
+        remove) play device-removed & ;;
+    esac
+done &
+
+# batteries already warned about, each one warns again once it charged or recovered
+(
+    warned=" "
+    while sleep 60; do
+        for bat in /sys/class/power_supply/BAT*; do
+            [ -r "$bat/capacity" ] || continue
+            name=${bat##*/}
+            if [ "$(cat "$bat/status")" = "Discharging" ] && [ "$(cat "$bat/capacity")" -le 15 ]; then
+                case "$warned" in
+                    *" $name "*) ;;
+                    *) play battery-low; warned="$warned$name " ;;
+                esac
+            else
+                warned=$(echo "$warned" | sed "s/ $name / /")
+            fi
+        done
+    done
+) &
+
+# grim, grimblast and hyprshot save to $XDG_SCREENSHOTS_DIR or the pictures folder
+pictures=$(xdg-user-dir PICTURES 2>/dev/null || echo "$HOME/Pictures")
+for dir in "$XDG_SCREENSHOTS_DIR" "$pictures" "$pictures/Screenshots"; do
+    [ -n "$dir" ] && [ -d "$dir" ] && echo "$dir"
+done | sort -u | tr '\n' '\0' | xargs -0 -r inotifywait -m -q -e close_write -e moved_to --format '%f' 2>/dev/null |
+    grep --line-buffered -i '\.\(png\|jpe\?g\|webp\)$' | while read -r _; do
+    play screenshot &
+done &
+
+last=$(pactl get-sink-volume @DEFAULT_SINK@ 2>/dev/null)
+pactl subscribe 2>/dev/null | grep --line-buffered "'change' on sink " | while read -r _; do
+    now=$(pactl get-sink-volume @DEFAULT_SINK@ 2>/dev/null)
+    [ "$now" != "$last" ] && play volume &
+    last=$now
+done &
+
+wait
+"#;
+
+fn event_sounds_path() -> PathBuf {
+    let home_dir = env::var("HOME").unwrap_or_default();
+    PathBuf::from(format!("{}/.config/hypr/sound/events.ctl", home_dir))
+}
+
+fn event_sound_script_path() -> PathBuf {
+    let home_dir = env::var("HOME").unwrap_or_default();
+    PathBuf::from(format!("{}/.config/hypr/scripts/eventsound.sh", home_dir))
+}
+
+fn load_event_sounds() -> HashMap<String, PathBuf> {
+    fs::read_to_string(event_sounds_path())
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| line.split_once('|'))
+        .map(|(event, path)| (event.to_string(), PathBuf::from(path)))
+        .collect()
+}
+
+fn save_event_sounds(sounds: &HashMap<String, PathBuf>) -> std::io::Result<()> {
+    let path = event_sounds_path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let content: String = SOUND_EVENTS
+        .iter()
+        .filter_map(|(event, _, _)| sounds.get(*event).map(|p| format!("{}|{}\n", event, p.display())))
+        .collect();
+    fs::write(path, content)
+}
+
+/// Writes the hook script and enables its user service while any event has a sound.
+/// systemctl runs in the background, a failure shows up in the footer.
+fn install_event_sound_hook(enabled: bool, notif_box: &GtkBox) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let script = event_sound_script_path();
+    if let Some(parent) = script.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    if fs::read_to_string(&script).unwrap_or_default() != EVENT_SOUND_SCRIPT {
+        fs::write(&script, EVENT_SOUND_SCRIPT)?;
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755))?;
+    }
+
+    let home_dir = env::var("HOME").unwrap_or_default();
+    let unit_dir = PathBuf::from(format!("{}/.config/systemd/user", home_dir));
+    fs::create_dir_all(&unit_dir)?;
+    // KillMode=mixed sends SIGTERM to the script alone first, so the logout sound gets to play
+    let unit = format!(
+        "[Unit]\nDescription=calibrate event sounds\n\n[Service]\nExecStart={} --watch\nKillMode=mixed\n\n[Install]\nWantedBy=default.target\n",
+        script.display()
+    );
+    let unit_path = unit_dir.join("calibrate-eventsound.service");
+    let reload = fs::read_to_string(&unit_path).unwrap_or_default() != unit;
+    if reload {
+        fs::write(&unit_path, unit)?;
+    }
+
+    let action = if enabled { "enable" } else { "disable" };
+    let notif_box = notif_box.clone();
+    glib::MainContext::default().spawn_local(async move {
+        let systemctl = |args: &[&str]| {
+            let argv: Vec<&std::ffi::OsStr> = ["systemctl", "--user"].iter().chain(args).map(std::ffi::OsStr::new).collect();
+            gtk4::gio::Subprocess::newv(&argv, gtk4::gio::SubprocessFlags::STDERR_SILENCE)
+        };
+        if let Some(Ok(process)) = reload.then(|| systemctl(&["daemon-reload"])) {
+            let _ = process.wait_future().await;
+        }
+        let result = match systemctl(&[action, "--now", "calibrate-eventsound.service"]) {
+            Ok(process) => process.wait_check_future().await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            show_notification(&notif_box, &format!("could not {} the event sound service: {}", action, e));
+        }
+    });
+    Ok(())
+}
+
+fn sound_theme_name() -> String {
+    Command::new("gsettings")
+        .args(["get", "org.gnome.desktop.sound", "theme-name"])
+        .output()
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().trim_matches('\'').to_string())
+        .unwrap_or_default()
+}
+
+/// `name` from the sound theme `theme`, falling back to freedesktop's.
+fn theme_sound(theme: &str, name: &str) -> Option<PathBuf> {
+    let home_dir = env::var("HOME").unwrap_or_default();
+    let roots = [format!("{}/.local/share/sounds", home_dir), "/usr/share/sounds".to_string()];
+    let themes = [theme, "freedesktop"];
+    themes
+        .iter()
+        .filter(|t| !t.is_empty())
+        .flat_map(|t| roots.iter().map(move |root| PathBuf::from(format!("{}/{}/stereo", root, t))))
+        .flat_map(|dir| ["oga", "ogg", "wav"].map(|ext| dir.join(format!("{}.{}", name, ext))))
+        .find(|path| path.is_file())
+}
+
+#[derive(Clone)]
+struct EventSoundsPage {
+    sounds: Rc<RefCell<HashMap<String, PathBuf>>>,
+    preview: SoundPreview,
+    notif_box: GtkBox,
+}
+
+impl EventSoundsPage {
+    fn set(&self, event: &str, sound: Option<PathBuf>) {
+        let mut sounds = self.sounds.borrow_mut();
+        match sound {
+            Some(path) => sounds.insert(event.to_string(), path),
+            None => sounds.remove(event),
+        };
+        let result = save_event_sounds(&sounds).and_then(|_| install_event_sound_hook(!sounds.is_empty(), &self.notif_box));
+        match result {
+            Ok(()) => show_notification(&self.notif_box, "Event sound modified"),
+            Err(e) => show_notification(&self.notif_box, &format!("could not save event sounds: {}", e)),
+        }
+    }
+}
+
+fn build_event_sounds_page(notif_box: &GtkBox, window: &ApplicationWindow) -> (GtkBox, SoundPreview) {
+    let page_box = GtkBox::new(Orientation::Vertical, 10);
+    let page = EventSoundsPage {
+        sounds: Rc::new(RefCell::new(load_event_sounds())),
+        preview: Rc::new(RefCell::new(None)),
+        notif_box: notif_box.clone(),
+    };
+    let home_dir = env::var("HOME").unwrap_or_default();
+    let events_dir = PathBuf::from(format!("{}/.config/hypr/sound/events", home_dir));
+    let mut user_sounds: Vec<PathBuf> = fs::read_dir(&events_dir)
+        .map(|entries| entries.flatten().map(|e| e.path()).filter(|p| p.is_file()).collect())
+        .unwrap_or_default();
+    user_sounds.sort();
+
+    let hint = Label::new(Some(&format!(
+        "Screenshots saved to your pictures folder play the screenshot sound, other tools and your own binds can run {} <event>",
+        event_sound_script_path().display()
+    )));
+    hint.set_halign(gtk4::Align::Start);
+    hint.set_wrap(true);
+    page_box.append(&hint);
+
+    let sound_theme = sound_theme_name();
+    for (event, label, theme_name) in SOUND_EVENTS {
+        let event_label = Label::new(Some(label));
+        event_label.set_halign(gtk4::Align::Start);
+        let row = GtkBox::new(Orientation::Horizontal, 5);
+        let row_scroller = ScrolledWindow::builder()
+            .hscrollbar_policy(gtk4::PolicyType::Automatic)
+            .vscrollbar_policy(gtk4::PolicyType::Never)
+            .child(&row)
+            .build();
+        page_box.append(&event_label);
+        page_box.append(&row_scroller);
+
+        let current = page.sounds.borrow().get(event).cloned();
+        let selected_button: Rc<RefCell<Option<Button>>> = Rc::new(RefCell::new(None));
+
+        let page_clone = page.clone();
+        add_sound_button(&row, "None", None, current.is_none(), &selected_button, &page.preview, move || {
+            page_clone.set(event, None);
+        });
+
+        let mut choices: Vec<PathBuf> = theme_sound(&sound_theme, theme_name).into_iter().collect();
+        choices.extend(user_sounds.iter().cloned());
+        if let Some(current) = current.as_ref().filter(|c| !choices.contains(c)) {
+            choices.push(current.clone());
+        }
+        for path in choices {
+            let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+            let selected = current.as_ref() == Some(&path);
+            let page_clone = page.clone();
+            let path_clone = path.clone();
+            add_sound_button(&row, &name, Some(&path), selected, &selected_button, &page.preview, move || {
+                page_clone.set(event, Some(path_clone.clone()));
+            });
+        }
+
+        // a file of the user's own, copied next to the other event sounds and selected
+        let choose = Button::with_label("Choose file…");
+        choose.set_valign(gtk4::Align::Center);
+        row.append(&choose);
+        let page_clone = page.clone();
+        let window_clone = window.clone();
+        let events_dir = events_dir.clone();
+        choose.connect_clicked(move |choose| {
+            let dialog = FileChooserDialog::new(
+                Some("Select a sound"),
+                Some(&window_clone),
+                FileChooserAction::Open,
+                &[("_Cancel", ResponseType::Cancel), ("_Select", ResponseType::Accept)],
+            );
+            dialog.set_css_classes(&["wall-dialog"]);
+            dialog.set_size_request(400, 800);
+            add_class_recursive(dialog.upcast_ref(), "wall-dialog");
+            let audio_filter = gtk4::FileFilter::new();
+            audio_filter.set_name(Some("Audio"));
+            audio_filter.add_mime_type("audio/*");
+            dialog.add_filter(&audio_filter);
+
+            let page_inner = page_clone.clone();
+            let events_dir = events_dir.clone();
+            let choose = choose.clone();
+            let selected_button = selected_button.clone();
+            dialog.connect_response(move |dialog, response| {
+                let source = dialog.file().and_then(|f| f.path());
+                dialog.close();
+                let (ResponseType::Accept, Some(source)) = (response, source) else {
+                    return;
+                };
+                let options = SoundImportOptions { max_length: None, normalise: false };
+                let (choose, page_inner, events_dir, selected_button) =
+                    (choose.clone(), page_inner.clone(), events_dir.clone(), selected_button.clone());
+                glib::MainContext::default().spawn_local(async move {
+                    match import_startup_sound(&source, &events_dir, options).await {
+                        Ok(dest) => {
+                            let Some(row) = choose.parent().and_downcast::<GtkBox>() else {
+                                return;
+                            };
+                            let name = dest.file_name().unwrap_or_default().to_string_lossy().to_string();
+                            let page_select = page_inner.clone();
+                            let dest_clone = dest.clone();
+                            let btn = add_sound_button(&row, &name, Some(&dest), false, &selected_button, &page_inner.preview, move || {
+                                page_select.set(event, Some(dest_clone.clone()));
+                            });
+                            // keep "Choose file…" last
+                            row.reorder_child_after(&choose, row.last_child().as_ref());
+                            btn.emit_clicked();
+                        }
+                        Err(e) => show_notification(&page_inner.notif_box, &format!("could not use {}: {}", source.display(), e)),
+                    }
+                });
+            });
+            dialog.show();
+        });
+    }
+
+    (page_box, page.preview)
+}
+
+#[derive(Clone, Copy)]
+struct SoundImportOptions {
+    // seconds, None keeps the whole file
+    max_length: Option<f64>,
+    normalise: bool,
+}
+
+/// True when ffprobe finds an audio stream it can decode.
+async fn is_decodable_audio(path: &Path) -> bool {
+    let probe = ["ffprobe", "-v", "error", "-select_streams", "a:0", "-show_entries", "stream=codec_name", "-of", "csv=p=0"];
+    let mut argv: Vec<&std::ffi::OsStr> = probe.iter().map(std::ffi::OsStr::new).collect();
+    argv.push(path.as_os_str());
+    let flags = gtk4::gio::SubprocessFlags::STDOUT_PIPE | gtk4::gio::SubprocessFlags::STDERR_SILENCE;
+    let Ok(process) = gtk4::gio::Subprocess::newv(&argv, flags) else {
+        return false;
+    };
+    match process.communicate_utf8_future(None).await {
+        Ok((stdout, _)) => process.is_successful() && stdout.is_some_and(|out| !out.trim().is_empty()),
+        Err(_) => false,
+    }
+}
+
+/// First free `<stem>.<ext>`, `<stem>-1.<ext>`, … in `dir`.
+fn unique_destination(dir: &Path, stem: &str, ext: &str) -> PathBuf {
+    let mut dest = dir.join(format!("{}.{}", stem, ext));
+    let mut n = 1;
+    while dest.exists() {
+        dest = dir.join(format!("{}-{}.{}", stem, n, ext));
+        n += 1;
+    }
+    dest
+}
+
+/// ffprobe and ffmpeg run as gio::Subprocess, so awaiting this keeps the UI responsive.
+async fn import_startup_sound(source: &Path, sound_dir: &Path, options: SoundImportOptions) -> Result<PathBuf, String> {
+    if !is_decodable_audio(source).await {
+        return Err("not a playable audio file".to_string());
+    }
+    fs::create_dir_all(sound_dir).map_err(|e| e.to_string())?;
+    let stem = source.file_stem().unwrap_or_default().to_string_lossy().to_string();
+
+    if options.max_length.is_none() && !options.normalise {
+        let ext = source.extension().unwrap_or_default().to_string_lossy().to_string();
+        let dest = unique_destination(sound_dir, &stem, &ext);
+        fs::copy(source, &dest).map_err(|e| e.to_string())?;
+        return Ok(dest);
+    }
+
+    // re-encoded to flac, which mpv, pw-play and paplay all read
+    let dest = unique_destination(sound_dir, &stem, "flac");
+    let mut args: Vec<std::ffi::OsString> = vec!["ffmpeg".into(), "-nostdin".into(), "-v".into(), "error".into(), "-y".into(), "-i".into()];
+    args.push(source.into());
+    args.push("-vn".into());
+    if let Some(seconds) = options.max_length {
+        args.extend(["-t".into(), format!("{}", seconds).into()]);
+    }
+    if options.normalise {
+        args.extend(["-af".into(), "loudnorm=I=-16:TP=-1.5:LRA=11".into()]);
+    }
+    args.push(dest.clone().into());
+    let argv: Vec<&std::ffi::OsStr> = args.iter().map(|a| a.as_os_str()).collect();
+    let process = gtk4::gio::Subprocess::newv(&argv, gtk4::gio::SubprocessFlags::NONE)
+        .map_err(|e| format!("could not run ffmpeg: {}", e))?;
+    match process.wait_check_future().await {
+        Ok(()) => Ok(dest),
+        Err(_) => {
+            let _ = fs::remove_file(&dest);
+            Err("ffmpeg could not convert it".to_string())
+        }
+    }
+}
+
+fn import_startup_sounds(paths: Vec<PathBuf>, options: SoundImportOptions, page: &StartupSoundPage) {
+    let files: Vec<PathBuf> = paths.into_iter().filter(|p| p.is_file()).collect();
+    if files.is_empty() {
+        show_notification(&page.notif_box, "nothing to import");
+        return;
+    }
+    let home_dir = env::var("HOME").unwrap_or_default();
+    let sound_dir = PathBuf::from(format!("{}/.config/hypr/sound/startup", home_dir));
+
+    let total = files.len();
+    let page = page.clone();
+
+    // one file after the other so the footer can show progress
+    glib::MainContext::default().spawn_local(async move {
+        let mut added = 0;
+        let mut failed: Vec<String> = Vec::new();
+        for (i, path) in files.into_iter().enumerate() {
+            let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+            show_notification(&page.notif_box, &format!("importing {}/{}: {}", i + 1, total, name));
+            match import_startup_sound(&path, &sound_dir, options).await {
+                Ok(dest) => {
+                    add_startup_sound_button(&page, Some(&dest));
+                    added += 1;
+                }
+                Err(e) => failed.push(format!("{} ({})", name, e)),
+            }
+        }
+        let mut summary = format!("imported {} of {} sounds", added, total);
+        if !failed.is_empty() {
+            summary.push_str(&format!(", failed: {}", failed.join(", ")));
+        }
+        show_notification(&page.notif_box, &summary);
+    });
+}
+
+// Autostart -------------------------------------------------------------------------------------------------------------------------------------- //
+// exec lines from hyprland.conf and every file it sources, plus XDG autostart entries.
+// Disabling a Hyprland entry comments its line out behind AUTOSTART_DISABLED_MARKER, other
+// comments are left alone; disabling an XDG entry writes a Hidden=true copy to
+// ~/.config/autostart, which shadows the system file.
+
+const EXEC_KEYWORDS: [&str; 4] = ["exec-once", "exec", "execr-once", "execr"];
+const AUTOSTART_DISABLED_MARKER: &str = "# calibrate:disabled";
+
+#[derive(Clone)]
+enum AutostartSource {
+    // 0-based line in a Hyprland config file
+    Hypr { file: PathBuf, line: usize },
+    Xdg { file: PathBuf },
+}
+
+#[derive(Clone)]
+struct AutostartEntry {
+    source: AutostartSource,
+    keyword: String,
+    command: String,
+    enabled: bool,
+}
+
+fn expand_config_path(path: &str, base_dir: &Path) -> PathBuf {
+    let home_dir = env::var("HOME").unwrap_or_default();
+    let expanded = path.replacen("$HOME", &home_dir, 1);
+    let expanded = match expanded.strip_prefix("~/") {
+        Some(rest) => format!("{}/{}", home_dir, rest),
+        None => expanded,
+    };
+    let path = PathBuf::from(expanded);
+    if path.is_absolute() { path } else { base_dir.join(path) }
+}
+
+/// hyprland.conf followed by everything it sources, recursively, in load order.
+fn hyprland_config_files() -> Vec<PathBuf> {
+    let home_dir = env::var("HOME").unwrap_or_default();
+    let mut files = Vec::new();
+    let mut pending = vec![PathBuf::from(format!("{}/.config/hypr/hyprland.conf", home_dir))];
+
+    while let Some(file) = pending.pop() {
+        if files.contains(&file) {
+            continue;
+        }
+        let Ok(content) = fs::read_to_string(&file) else {
+            continue;
+        };
+        let base_dir = file.parent().map(Path::to_path_buf).unwrap_or_default();
+        let mut sourced = Vec::new();
+        for line in content.lines() {
+            let Some((key, value)) = line.trim().split_once('=') else {
+                continue;
+            };
+            if key.trim() != "source" {
+                continue;
+            }
+            let target = expand_config_path(value.split('#').next().unwrap_or("").trim(), &base_dir);
+            // `source = dir/*.conf` style globs, only in the file name
+            let pattern = target.file_name().unwrap_or_default().to_string_lossy().to_string();
+            match pattern.split_once('*') {
+                Some((prefix, suffix)) => {
+                    let dir = target.parent().map(Path::to_path_buf).unwrap_or_default();
+                    let mut matches: Vec<PathBuf> = fs::read_dir(&dir)
+                        .map(|entries| entries.flatten().map(|e| e.path()).collect())
+                        .unwrap_or_default();
+                    matches.retain(|p| {
+                        let name = p.file_name().unwrap_or_default().to_string_lossy();
+                        name.starts_with(prefix) && name.ends_with(suffix)
+                    });
+                    matches.sort();
+                    sourced.extend(matches);
+                }
+                None => sourced.push(target),
+            }
+        }
+        files.push(file);
+        // pop takes from the back, so push in reverse to keep load order
+        pending.extend(sourced.into_iter().rev());
+    }
+    files
+}
+
+/// (keyword, command, enabled) for `exec-once = cmd` and its commented-out form.
+fn parse_exec_line(line: &str) -> Option<(String, String, bool)> {
+    let trimmed = line.trim();
+    // only lines this page commented out are disabled entries, anything else commented is a comment
+    let (uncommented, enabled) = match trimmed.strip_prefix(AUTOSTART_DISABLED_MARKER) {
+        Some(rest) => (rest.trim_start(), false),
+        None if trimmed.starts_with('#') => return None,
+        None => (trimmed, true),
+    };
+    let (key, command) = uncommented.split_once('=')?;
+    let key = key.trim();
+    if !EXEC_KEYWORDS.contains(&key) || command.trim().is_empty() {
+        return None;
+    }
+    Some((key.to_string(), command.trim().to_string(), enabled))
+}
+
+fn hypr_autostart_entries() -> Vec<AutostartEntry> {
+    let mut entries = Vec::new();
+    for file in hyprland_config_files() {
+        let content = fs::read_to_string(&file).unwrap_or_default();
+        for (line, text) in content.lines().enumerate() {
+            if let Some((keyword, command, enabled)) = parse_exec_line(text) {
+                entries.push(AutostartEntry {
+                    source: AutostartSource::Hypr { file: file.clone(), line },
+                    keyword,
+                    command,
+                    enabled,
+                });
+            }
+        }
+    }
+    entries
+}
+
+fn user_autostart_dir() -> PathBuf {
+    let home_dir = env::var("HOME").unwrap_or_default();
+    PathBuf::from(format!("{}/.config/autostart", home_dir))
+}
+
+fn xdg_autostart_entries() -> Vec<AutostartEntry> {
+    // user files shadow system files of the same name
+    let mut by_name: HashMap<String, PathBuf> = HashMap::new();
+    for dir in [PathBuf::from("/etc/xdg/autostart"), user_autostart_dir()] {
+        let Ok(dir_entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for path in dir_entries.flatten().map(|e| e.path()) {
+            if path.extension().is_some_and(|ext| ext == "desktop") {
+                by_name.insert(path.file_name().unwrap_or_default().to_string_lossy().to_string(), path);
+            }
+        }
+    }
+
+    let mut names: Vec<&String> = by_name.keys().collect();
+    names.sort();
+    names
+        .into_iter()
+        .filter_map(|name| {
+            let file = by_name[name].clone();
+            let key_file = glib::KeyFile::new();
+            key_file.load_from_file(&file, glib::KeyFileFlags::NONE).ok()?;
+            let command = key_file.string("Desktop Entry", "Exec").ok()?.to_string();
+            let title = key_file.locale_string("Desktop Entry", "Name", None).map(|n| n.to_string()).unwrap_or(command);
+            let hidden = key_file.boolean("Desktop Entry", "Hidden").unwrap_or(false);
+            let gnome_enabled = key_file.boolean("Desktop Entry", "X-GNOME-Autostart-enabled").unwrap_or(true);
+            Some(AutostartEntry {
+                source: AutostartSource::Xdg { file },
+                keyword: "XDG".to_string(),
+                command: title,
+                enabled: !hidden && gnome_enabled,
+            })
+        })
+        .collect()
+}
+
+fn edit_config_lines(file: &Path, edit: impl FnOnce(&mut Vec<String>)) -> std::io::Result<()> {
+    let content = fs::read_to_string(file)?;
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+    edit(&mut lines);
+    let mut updated = lines.join("\n");
+    if content.ends_with('\n') && !updated.is_empty() {
+        updated.push('\n');
+    }
+    fs::write(file, updated)
+}
+
+fn set_autostart_enabled(entry: &AutostartEntry, enabled: bool) -> std::io::Result<()> {
+    match &entry.source {
+        AutostartSource::Hypr { file, line } => edit_config_lines(file, |lines| {
+            if let Some(text) = lines.get_mut(*line) {
+                let indent = &text[..text.len() - text.trim_start().len()];
+                let body = split_comment_prefix(text).1;
+                *text = if enabled {
+                    format!("{}{}", indent, body)
+                } else {
+                    format!("{}{} {}", indent, AUTOSTART_DISABLED_MARKER, body)
+                };
+            }
+        }),
+        AutostartSource::Xdg { file } => {
+            let key_file = glib::KeyFile::new();
+            key_file
+                .load_from_file(file, glib::KeyFileFlags::KEEP_COMMENTS | glib::KeyFileFlags::KEEP_TRANSLATIONS)
+                .map_err(std::io::Error::other)?;
+            key_file.set_boolean("Desktop Entry", "Hidden", !enabled);
+            if key_file.has_key("Desktop Entry", "X-GNOME-Autostart-enabled").unwrap_or(false) {
+                key_file.set_boolean("Desktop Entry", "X-GNOME-Autostart-enabled", enabled);
+            }
+            // system files are overridden from the user directory rather than edited
+            let dest = user_autostart_dir().join(file.file_name().unwrap_or_default());
+            fs::create_dir_all(user_autostart_dir())?;
+            key_file.save_to_file(&dest).map_err(std::io::Error::other)
+        }
+    }
+}
+
+fn delete_autostart_entry(entry: &AutostartEntry) -> std::io::Result<()> {
+    match &entry.source {
+        AutostartSource::Hypr { file, line } => edit_config_lines(file, |lines| {
+            if *line < lines.len() {
+                lines.remove(*line);
+            }
+        }),
+        AutostartSource::Xdg { file } if file.starts_with(user_autostart_dir()) => fs::remove_file(file),
+        // a system entry can only be hidden
+        AutostartSource::Xdg { .. } => set_autostart_enabled(entry, false),
+    }
+}
+
+/// Swaps a Hyprland entry with the previous (or next) exec line of the same file.
+fn move_autostart_entry(entry: &AutostartEntry, entries: &[AutostartEntry], up: bool) -> std::io::Result<()> {
+    let AutostartSource::Hypr { file, line } = &entry.source else {
+        return Ok(());
+    };
+    let same_file = entries.iter().filter_map(|e| match &e.source {
+        AutostartSource::Hypr { file: f, line: l } if f == file => Some(*l),
+        _ => None,
+    });
+    let neighbour = if up {
+        same_file.filter(|l| l < line).max()
+    } else {
+        same_file.filter(|l| l > line).min()
+    };
+    let Some(neighbour) = neighbour else {
+        return Ok(());
+    };
+    edit_config_lines(file, |lines| {
+        if *line < lines.len() && neighbour < lines.len() {
+            lines.swap(*line, neighbour);
+        }
+    })
+}
+
+/// Appends `exec-once = <app>` to startup.conf.
+fn add_autostart_app(app: &gtk4::gio::AppInfo) -> std::io::Result<()> {
+    let home_dir = env::var("HOME").unwrap_or_default();
+    let config_path = PathBuf::from(format!("{}/.config/hypr/startup.conf", home_dir));
+    let commandline = app.commandline().unwrap_or_default().to_string_lossy().to_string();
+    // drop desktop entry field codes like %U
+    let command: Vec<&str> = commandline.split_whitespace().filter(|arg| !(arg.len() == 2 && arg.starts_with('%'))).collect();
+    if command.is_empty() {
+        return Err(std::io::Error::other("the application has no command"));
+    }
+    if !config_path.exists() {
+        fs::write(&config_path, "")?;
+    }
+    edit_config_lines(&config_path, |lines| lines.push(format!("exec-once = {}", command.join(" "))))
+}
+
+fn fill_autostart_list(list: &gtk4::ListBox, notif_box: &GtkBox) {
+    while let Some(child) = list.first_child() {
+        list.remove(&child);
+    }
+    let home_dir = env::var("HOME").unwrap_or_default();
+    let hypr_entries = hypr_autostart_entries();
+    let mut entries = hypr_entries.clone();
+    entries.extend(xdg_autostart_entries());
+
+    for entry in entries {
+        let row = GtkBox::new(Orientation::Horizontal, 10);
+        let switch = Switch::builder().active(entry.enabled).valign(gtk4::Align::Center).build();
+        let text = GtkBox::new(Orientation::Vertical, 2);
+        text.set_hexpand(true);
+        let command = Label::new(Some(&entry.command));
+        command.set_halign(gtk4::Align::Start);
+        command.set_ellipsize(gtk4::pango::EllipsizeMode::End);
+        let origin = match &entry.source {
+            AutostartSource::Hypr { file, line } => format!("{}  {}:{}", entry.keyword, file.display(), line + 1),
+            AutostartSource::Xdg { file } => format!("XDG autostart  {}", file.display()),
+        };
+        let origin = Label::new(Some(&origin.replace(&home_dir, "~")));
+        origin.set_halign(gtk4::Align::Start);
+        origin.set_opacity(0.6);
+        text.append(&command);
+        text.append(&origin);
+        row.append(&switch);
+        row.append(&text);
+
+        let reorderable = matches!(entry.source, AutostartSource::Hypr { .. });
+        let up = Button::with_label("↑");
+        let down = Button::with_label("↓");
+        let delete = Button::with_label("✕");
+        up.set_sensitive(reorderable);
+        down.set_sensitive(reorderable);
+        for btn in [&up, &down, &delete] {
+            btn.set_valign(gtk4::Align::Center);
+            row.append(btn);
+        }
+        list.append(&row);
+
+        let entry = Rc::new(entry);
+        let hypr_entries = Rc::new(hypr_entries.clone());
+        let act = {
+            let list = list.clone();
+            let notif_box = notif_box.clone();
+            move |result: std::io::Result<()>, done: &str| {
+                match result {
+                    Ok(()) => show_notification(&notif_box, done),
+                    Err(e) => show_notification(&notif_box, &format!("autostart not changed: {}", e)),
+                }
+                // line numbers shift after any edit
+                let list = list.clone();
+                let notif_box = notif_box.clone();
+                glib::idle_add_local_once(move || fill_autostart_list(&list, &notif_box));
+            }
+        };
+
+        let (entry_clone, act_clone) = (entry.clone(), act.clone());
+        switch.connect_state_set(move |_, state| {
+            let done = if state { "autostart entry enabled" } else { "autostart entry disabled" };
+            act_clone(set_autostart_enabled(&entry_clone, state), done);
+            glib::Propagation::Proceed
+        });
+        for (btn, move_up) in [(&up, true), (&down, false)] {
+            let (entry_clone, act_clone, hypr_entries) = (entry.clone(), act.clone(), hypr_entries.clone());
+            btn.connect_clicked(move |_| {
+                act_clone(move_autostart_entry(&entry_clone, &hypr_entries, move_up), "autostart order changed");
+            });
+        }
+        let list = list.clone();
+        delete.connect_clicked(move |_| {
+            let dialog = MessageDialog::builder()
+                .text("Remove this autostart entry?")
+                .secondary_text(&entry.command)
+                .modal(true)
+                .build();
+            if let Some(window) = list.root().and_downcast::<gtk4::Window>() {
+                dialog.set_transient_for(Some(&window));
+            }
+            dialog.add_buttons(&[("Cancel", ResponseType::Cancel), ("Remove", ResponseType::Accept)]);
+            let (entry, act) = (entry.clone(), act.clone());
+            dialog.connect_response(move |dialog, response| {
+                dialog.close();
+                if response == ResponseType::Accept {
+                    act(delete_autostart_entry(&entry), "autostart entry removed");
+                }
+            });
+            dialog.show();
+        });
+    }
+}
+
+fn open_autostart_app_picker(list: &gtk4::ListBox, notif_box: &GtkBox, window: &ApplicationWindow) {
+    let picker = gtk4::Window::builder()
+        .title("Add to autostart")
+        .modal(true)
+        .transient_for(window)
+        .default_width(400)
+        .default_height(500)
+        .build();
+    let picker_box = GtkBox::new(Orientation::Vertical, 5);
+    let search = gtk4::SearchEntry::new();
+    let apps_list = gtk4::ListBox::new();
+    let scroller = ScrolledWindow::builder().child(&apps_list).vexpand(true).build();
+    picker_box.append(&search);
+    picker_box.append(&scroller);
+    picker.set_child(Some(&picker_box));
+
+    let mut apps: Vec<gtk4::gio::AppInfo> = gtk4::gio::AppInfo::all().into_iter().filter(|a| a.should_show()).collect();
+    apps.sort_by_key(|a| a.display_name().to_lowercase());
+    for app in &apps {
+        let label = Label::new(Some(&app.display_name()));
+        label.set_halign(gtk4::Align::Start);
+        apps_list.append(&label);
+    }
+
+    let search_clone = search.clone();
+    let names: Vec<String> = apps.iter().map(|a| a.display_name().to_lowercase()).collect();
+    apps_list.set_filter_func(move |row| {
+        let query = search_clone.text().to_lowercase();
+        names.get(row.index() as usize).is_some_and(|name| name.contains(&query))
+    });
+    let apps_list_clone = apps_list.clone();
+    search.connect_search_changed(move |_| apps_list_clone.invalidate_filter());
+
+    let (list, notif_box, picker_clone) = (list.clone(), notif_box.clone(), picker.clone());
+    apps_list.connect_row_activated(move |_, row| {
+        let Some(app) = apps.get(row.index() as usize) else {
+            return;
+        };
+        match add_autostart_app(app) {
+            Ok(()) => show_notification(&notif_box, &format!("{} added to autostart", app.display_name())),
+            Err(e) => show_notification(&notif_box, &format!("could not add {}: {}", app.display_name(), e)),
+        }
+        fill_autostart_list(&list, &notif_box);
+        picker_clone.close();
+    });
+
+    picker.present();
+}
+
+fn build_autostart_page(notif_box: &GtkBox, window: &ApplicationWindow) -> GtkBox {
+    let page = GtkBox::new(Orientation::Vertical, 10);
+    let header = GtkBox::new(Orientation::Horizontal, 5);
+    header.set_halign(gtk4::Align::End);
+    let add = Button::with_label("Add application");
+    let reload = Button::with_label("Reload");
+    header.append(&reload);
+    header.append(&add);
+
+    let list = gtk4::ListBox::new();
+    list.set_selection_mode(gtk4::SelectionMode::None);
+    let scroller = ScrolledWindow::builder().child(&list).vexpand(true).build();
+    page.append(&header);
+    page.append(&scroller);
+    fill_autostart_list(&list, notif_box);
+
+    let (list_clone, notif_clone) = (list.clone(), notif_box.clone());
+    reload.connect_clicked(move |_| fill_autostart_list(&list_clone, &notif_clone));
+    let (notif_clone, window_clone) = (notif_box.clone(), window.clone());
+    add.connect_clicked(move |_| open_autostart_app_picker(&list, &notif_clone, &window_clone));
+
+    page
+}
+
+// NetworkManager ---------------------------------------------------------------------------------------------------------------------------------- //
+// One system bus connection is shared by the network page. It is a LocalConnection so match
+// callbacks can hold GTK objects, and it is dispatched from the GLib main loop via its fd.
+
+const NM_BUS: &str = "org.freedesktop.NetworkManager";
+const NM_PATH: &str = "/org/freedesktop/NetworkManager";
+const NM_AGENT_PATH: &str = "/org/freedesktop/NetworkManager/SecretAgent";
+const NM_AGENT_ID: &str = "ekah.scu.calibrate";
+const NM_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+const NM_SETTINGS_IFACE: &str = "org.freedesktop.NetworkManager.Settings";
+const NM_CONNECTION_IFACE: &str = "org.freedesktop.NetworkManager.Settings.Connection";
+
+type NmConn = Rc<dbus::blocking::LocalConnection>;
+type NmSettings = HashMap<String, dbus::arg::PropMap>;
+
+thread_local! {
+    static NM_DISPATCH_QUEUED: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+}
+
+fn nm_connection() -> Option<NmConn> {
+    let mut channel = match dbus::channel::Channel::get_private(dbus::channel::BusType::System) {
+        Ok(channel) => channel,
+        Err(e) => {
+            eprintln!("no system bus: {}", e);
+            return None;
+        }
+    };
+    channel.set_watch_enabled(true);
+    let fd = channel.watch().fd;
+    let conn: NmConn = Rc::new(dbus::blocking::LocalConnection::from(channel));
+    // several pages listen to the same PropertiesChanged signals
+    conn.set_signal_match_mode(true);
+
+    let conn_weak = Rc::downgrade(&conn);
+    glib::source::unix_fd_add_local(fd, glib::IOCondition::IN, move |_, _| {
+        let Some(conn) = conn_weak.upgrade() else {
+            return glib::ControlFlow::Break;
+        };
+        nm_dispatch(&conn);
+        glib::ControlFlow::Continue
+    });
+    Some(conn)
+}
+
+fn nm_dispatch(conn: &dbus::blocking::LocalConnection) {
+    while let Ok(true) = conn.process(std::time::Duration::ZERO) {}
+}
+
+/// Blocking calls can pull signals off the socket into libdbus' queue, where the fd watch
+/// won't see them; handle those once the current callback is done.
+fn nm_dispatch_soon(conn: &NmConn) {
+    if NM_DISPATCH_QUEUED.with(|q| q.replace(true)) {
+        return;
+    }
+    let conn = conn.clone();
+    glib::idle_add_local_once(move || {
+        NM_DISPATCH_QUEUED.with(|q| q.set(false));
+        nm_dispatch(&conn);
+    });
+}
+
+fn nm_proxy<'a>(conn: &'a NmConn, path: &'a str) -> dbus::blocking::Proxy<'a, &'a dbus::blocking::LocalConnection> {
+    conn.with_proxy(NM_BUS, path, NM_TIMEOUT)
+}
+
+fn nm_prop<T: for<'b> dbus::arg::Get<'b> + 'static>(conn: &NmConn, path: &str, interface: &str, name: &str) -> Result<T, dbus::Error> {
+    use dbus::blocking::stdintf::org_freedesktop_dbus::Properties;
+    let result = nm_proxy(conn, path).get(interface, name);
+    nm_dispatch_soon(conn);
+    result
+}
+
+fn nm_call<R: dbus::arg::ReadAll, A: dbus::arg::AppendAll>(conn: &NmConn, path: &str, interface: &str, method: &str, args: A) -> Result<R, dbus::Error> {
+    let result = nm_proxy(conn, path).method_call(interface, method, args);
+    nm_dispatch_soon(conn);
+    result
+}
+
+fn settings_str(settings: &NmSettings, group: &str, key: &str) -> Option<String> {
+    settings.get(group).and_then(|g| dbus::arg::prop_cast::<String>(g, key)).cloned()
+}
+
+fn settings_ssid(settings: &NmSettings) -> Option<String> {
+    let ssid = settings.get("802-11-wireless").and_then(|g| dbus::arg::prop_cast::<Vec<u8>>(g, "ssid"))?;
+    Some(String::from_utf8_lossy(ssid).to_string())
+}
+
+/// Object paths and settings of every saved connection profile.
+fn nm_saved_connections(conn: &NmConn) -> Vec<(dbus::Path<'static>, NmSettings)> {
+    let settings_path = format!("{}/Settings", NM_PATH);
+    let Ok((paths,)) = nm_call::<(Vec<dbus::Path<'static>>,), _>(conn, &settings_path, NM_SETTINGS_IFACE, "ListConnections", ()) else {
+        return Vec::new();
+    };
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            let (settings,) = nm_call::<(NmSettings,), _>(conn, &path, NM_CONNECTION_IFACE, "GetSettings", ()).ok()?;
+            Some((path, settings))
+        })
+        .collect()
+}
+
+fn nm_saved_wifi_ssids(conn: &NmConn) -> HashSet<String> {
+    nm_saved_connections(conn).iter().filter_map(|(_, settings)| settings_ssid(settings)).collect()
+}
+
+// Wi-Fi security ---------------------------------------------------------------------------------------------------------------------------------- //
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+enum WifiSecurity {
+    Open,
+    Wep,
+    Psk,
+    Sae,
+    Owe,
+    Enterprise,
+}
+
+impl WifiSecurity {
+    /// From an access point's Flags, WpaFlags and RsnFlags.
+    fn from_ap_flags(flags: u32, wpa_flags: u32, rsn_flags: u32) -> Self {
+        const PRIVACY: u32 = 0x1;
+        const KEY_MGMT_PSK: u32 = 0x100;
+        const KEY_MGMT_802_1X: u32 = 0x200;
+        const KEY_MGMT_SAE: u32 = 0x400;
+        const KEY_MGMT_OWE: u32 = 0x800 | 0x1000;
+        let key_mgmt = wpa_flags | rsn_flags;
+        if key_mgmt & KEY_MGMT_802_1X != 0 {
+            WifiSecurity::Enterprise
+        } else if key_mgmt & KEY_MGMT_PSK != 0 {
+            // WPA2/WPA3 transition networks accept either, PSK works with more drivers
+            WifiSecurity::Psk
+        } else if key_mgmt & KEY_MGMT_SAE != 0 {
+            WifiSecurity::Sae
+        } else if key_mgmt & KEY_MGMT_OWE != 0 {
+            WifiSecurity::Owe
+        } else if flags & PRIVACY != 0 {
+            WifiSecurity::Wep
+        } else {
+            WifiSecurity::Open
+        }
+    }
+
+    fn from_key_mgmt(key_mgmt: &str) -> Self {
+        match key_mgmt {
+            "sae" => WifiSecurity::Sae,
+            "wpa-psk" => WifiSecurity::Psk,
+            "wpa-eap" | "wpa-eap-suite-b-192" | "ieee8021x" => WifiSecurity::Enterprise,
+            "owe" => WifiSecurity::Owe,
+            "none" => WifiSecurity::Wep,
+            _ => WifiSecurity::Open,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            WifiSecurity::Open => "Open",
+            WifiSecurity::Wep => "WEP",
+            WifiSecurity::Psk => "WPA/WPA2 Personal",
+            WifiSecurity::Sae => "WPA3 Personal",
+            WifiSecurity::Owe => "Enhanced Open",
+            WifiSecurity::Enterprise => "Enterprise",
+        }
+    }
+
+    fn needs_password(self) -> bool {
+        matches!(self, WifiSecurity::Wep | WifiSecurity::Psk | WifiSecurity::Sae)
+    }
+
+    fn is_valid_password(self, password: &str) -> bool {
+        let hex = |len: usize| password.len() == len && password.chars().all(|c| c.is_ascii_hexdigit());
+        match self {
+            WifiSecurity::Psk => (8..=63).contains(&password.len()) || hex(64),
+            // anything that isn't a key is hashed as a passphrase
+            WifiSecurity::Wep | WifiSecurity::Sae | WifiSecurity::Enterprise => !password.is_empty(),
+            _ => true,
+        }
+    }
+}
+
+/// NetworkManager's wep-key-type: 1 for a key (5 or 13 characters, 10 or 26 hex digits), 2 for a passphrase.
+fn wep_key_type(key: &str) -> u32 {
+    let hex = |len: usize| key.len() == len && key.chars().all(|c| c.is_ascii_hexdigit());
+    if key.len() == 5 || key.len() == 13 || hex(10) || hex(26) {
+        1
+    } else {
+        2
+    }
+}
+
+/// Asks for a network password; `on_done` gets None when cancelled. The returned window can be
+/// closed from outside, which also counts as cancelling.
+fn ask_wifi_password(
+    parent: Option<&gtk4::Window>,
+    ssid: &str,
+    security: WifiSecurity,
+    retry: bool,
+    on_done: impl FnOnce(Option<String>) + 'static,
+) -> gtk4::Window {
+    ask_wifi_secrets(parent, ssid, security, retry, None, move |secrets| on_done(secrets.map(|(_, password)| password)))
+}
+
+/// Like `ask_wifi_password`, with an identity entry above the password when `identity` is Some
+/// (prefilled with it); `on_done` gets (identity, password), the identity empty when not asked.
+fn ask_wifi_secrets(
+    parent: Option<&gtk4::Window>,
+    ssid: &str,
+    security: WifiSecurity,
+    retry: bool,
+    identity: Option<String>,
+    on_done: impl FnOnce(Option<(String, String)>) + 'static,
+) -> gtk4::Window {
+    let window = gtk4::Window::builder().title("Wi-Fi password").modal(true).resizable(false).build();
+    window.set_transient_for(parent);
+
+    let dialog_box = GtkBox::new(Orientation::Vertical, 10);
+    dialog_box.set_margin_top(15);
+    dialog_box.set_margin_bottom(15);
+    dialog_box.set_margin_start(15);
+    dialog_box.set_margin_end(15);
+    let heading = if retry {
+        format!("The password for \"{}\" was not accepted, try again", ssid)
+    } else {
+        format!("\"{}\" is secured with {}", ssid, security.label())
+    };
+    let heading = Label::new(Some(&heading));
+    heading.set_wrap(true);
+    heading.set_halign(gtk4::Align::Start);
+    let identity_entry = gtk4::Entry::builder()
+        .placeholder_text("Identity")
+        .text(identity.as_deref().unwrap_or_default())
+        .visible(identity.is_some())
+        .build();
+    let entry = gtk4::Entry::builder()
+        .visibility(false)
+        .input_purpose(gtk4::InputPurpose::Password)
+        .placeholder_text("Password")
+        .activates_default(true)
+        .build();
+    let show = gtk4::CheckButton::with_label("Show password");
+    let buttons = GtkBox::new(Orientation::Horizontal, 5);
+    buttons.set_halign(gtk4::Align::End);
+    let cancel = Button::with_label("Cancel");
+    let connect = Button::with_label("Connect");
+    connect.set_sensitive(false);
+    buttons.append(&cancel);
+    buttons.append(&connect);
+    dialog_box.append(&heading);
+    dialog_box.append(&identity_entry);
+    dialog_box.append(&entry);
+    dialog_box.append(&show);
+    dialog_box.append(&buttons);
+    window.set_child(Some(&dialog_box));
+    window.set_default_widget(Some(&connect));
+
+    let entry_clone = entry.clone();
+    show.connect_toggled(move |show| entry_clone.set_visibility(show.is_active()));
+    let update_connect = {
+        let (connect, entry, identity_entry) = (connect.clone(), entry.clone(), identity_entry.clone());
+        let needs_identity = identity.is_some();
+        move || {
+            let identity_ok = !needs_identity || !identity_entry.text().is_empty();
+            connect.set_sensitive(identity_ok && security.is_valid_password(&entry.text()));
+        }
+    };
+    let update = update_connect.clone();
+    entry.connect_changed(move |_| update());
+    identity_entry.connect_changed(move |_| update_connect());
+
+    // whichever way the window goes away, on_done runs once
+    type OnDone = Box<dyn FnOnce(Option<(String, String)>)>;
+    let on_done: Rc<RefCell<Option<OnDone>>> = Rc::new(RefCell::new(Some(Box::new(on_done))));
+    let on_done_clone = on_done.clone();
+    let window_clone = window.clone();
+    connect.connect_clicked(move |_| {
+        if let Some(done) = on_done_clone.borrow_mut().take() {
+            done(Some((identity_entry.text().to_string(), entry.text().to_string())));
+        }
+        window_clone.close();
+    });
+    let window_clone = window.clone();
+    cancel.connect_clicked(move |_| window_clone.close());
+    window.connect_close_request(move |_| {
+        if let Some(done) = on_done.borrow_mut().take() {
+            done(None);
+        }
+        glib::Propagation::Proceed
+    });
+
+    window.present();
+    window
+}
+
+// Secret agent ------------------------------------------------------------------------------------------------------------------------------------ //
+// Registered with NetworkManager's AgentManager so password re-prompts (a changed key, a
+// profile without a stored secret) come to calibrate instead of failing silently.
+
+const NM_AGENT_IFACE: &str = "org.freedesktop.NetworkManager.SecretAgent";
+
+fn agent_error(msg: &dbus::Message, name: &str, text: &str) -> dbus::Message {
+    let text = std::ffi::CString::new(text).unwrap_or_default();
+    msg.error(&dbus::strings::ErrorName::from(format!("{}.{}", NM_AGENT_IFACE, name)), &text)
+}
+
+/// Whether a message was sent by NetworkManager itself, the only caller the agent answers.
+fn sent_by_network_manager(conn: &dbus::blocking::LocalConnection, msg: &dbus::Message) -> bool {
+    let Some(sender) = msg.sender() else {
+        return false;
+    };
+    let bus = conn.with_proxy("org.freedesktop.DBus", "/org/freedesktop/DBus", NM_TIMEOUT);
+    match bus.method_call::<(String,), _, _, _>("org.freedesktop.DBus", "GetNameOwner", (NM_BUS,)) {
+        Ok((owner,)) => *sender == *owner,
+        Err(_) => false,
+    }
+}
+
+fn register_secret_agent(conn: &NmConn, window: &ApplicationWindow) {
+    use dbus::channel::{MatchingReceiver, Sender};
+
+    // open prompts by connection path, so CancelGetSecrets can close them
+    let prompts: Rc<RefCell<HashMap<String, gtk4::Window>>> = Rc::new(RefCell::new(HashMap::new()));
+    let conn_weak = Rc::downgrade(conn);
+    let window = window.clone();
+    let rule = dbus::message::MatchRule::new_method_call().with_path(NM_AGENT_PATH).with_interface(NM_AGENT_IFACE);
+
+    conn.start_receive(
+        rule,
+        Box::new(move |msg, conn| {
+            // anyone on the bus can call the agent's path, but only NetworkManager gets secrets
+            if !sent_by_network_manager(conn, &msg) {
+                let _ = conn.send(agent_error(&msg, "PermissionDenied", "only NetworkManager may call the agent"));
+                return true;
+            }
+            let member = msg.member().map(|m| m.to_string()).unwrap_or_default();
+            match member.as_str() {
+                "GetSecrets" => {
+                    let Ok((settings, path, setting_name, _hints, flags)) =
+                        msg.read5::<NmSettings, dbus::Path, String, Vec<String>, u32>()
+                    else {
+                        let _ = conn.send(agent_error(&msg, "InvalidConnection", "malformed request"));
+                        return true;
+                    };
+                    const ALLOW_INTERACTION: u32 = 0x1;
+                    const REQUEST_NEW: u32 = 0x2;
+                    let security = WifiSecurity::from_key_mgmt(
+                        &settings_str(&settings, "802-11-wireless-security", "key-mgmt").unwrap_or_default(),
+                    );
+                    let secret_key = match setting_name.as_str() {
+                        "802-11-wireless-security" if security == WifiSecurity::Wep => "wep-key0",
+                        "802-11-wireless-security" => "psk",
+                        "802-1x" => "password",
+                        _ => "",
+                    };
+                    // 802.1X asks for the identity along with its password
+                    let enterprise = setting_name == "802-1x";
+                    let identity = enterprise.then(|| settings_str(&settings, "802-1x", "identity").unwrap_or_default());
+                    if flags & ALLOW_INTERACTION == 0 || secret_key.is_empty() {
+                        let _ = conn.send(agent_error(&msg, "NoSecrets", "calibrate has no stored secrets"));
+                        return true;
+                    }
+
+                    let ssid = settings_ssid(&settings)
+                        .or_else(|| settings_str(&settings, "connection", "id"))
+                        .unwrap_or_default();
+                    let path = path.to_string();
+                    let conn_weak = conn_weak.clone();
+                    let prompts_clone = prompts.clone();
+                    let path_clone = path.clone();
+                    let setting_name_clone = setting_name.clone();
+                    let prompt = ask_wifi_secrets(
+                        Some(window.upcast_ref()),
+                        &ssid,
+                        if enterprise { WifiSecurity::Enterprise } else { security },
+                        flags & REQUEST_NEW != 0,
+                        identity,
+                        move |answer| {
+                            prompts_clone.borrow_mut().remove(&path_clone);
+                            let Some(conn) = conn_weak.upgrade() else {
+                                return;
+                            };
+                            let reply = match answer {
+                                Some((identity, password)) => {
+                                    let mut secrets = dbus::arg::PropMap::new();
+                                    if enterprise {
+                                        secrets.insert("identity".to_string(), dbus::arg::Variant(Box::new(identity)));
+                                    }
+                                    if secret_key == "wep-key0" {
+                                        let key_type = wep_key_type(&password);
+                                        secrets.insert("wep-key-type".to_string(), dbus::arg::Variant(Box::new(key_type)));
+                                    }
+                                    secrets.insert(secret_key.to_string(), dbus::arg::Variant(Box::new(password)));
+                                    let mut reply: NmSettings = HashMap::new();
+                                    reply.insert(setting_name_clone, secrets);
+                                    msg.method_return().append1(reply)
+                                }
+                                None => agent_error(&msg, "UserCanceled", "cancelled in calibrate"),
+                            };
+                            let _ = conn.send(reply);
+                            conn.channel().flush();
+                        },
+                    );
+                    prompts.borrow_mut().insert(path, prompt);
+                }
+                "CancelGetSecrets" => {
+                    if let Ok((path, _)) = msg.read2::<dbus::Path, String>() {
+                        // closing it answers the pending GetSecrets with UserCanceled
+                        let prompt = prompts.borrow_mut().remove(&path.to_string());
+                        if let Some(prompt) = prompt {
+                            prompt.close();
+                        }
+                    }
+                    let _ = conn.send(msg.method_return());
+                }
+                // secrets are stored by NetworkManager itself, nothing to save or delete here
+                "SaveSecrets" | "DeleteSecrets" => {
+                    let _ = conn.send(msg.method_return());
+                }
+                _ => {
+                    let _ = conn.send(agent_error(&msg, "Failed", "unknown method"));
+                }
+            }
+            true
+        }),
+    );
+
+    let agent_manager = format!("{}/AgentManager", NM_PATH);
+    if let Err(e) = nm_call::<(), _>(conn, &agent_manager, "org.freedesktop.NetworkManager.AgentManager", "Register", (NM_AGENT_ID,)) {
+        eprintln!("could not register the secret agent: {}", e);
+    }
+}
+
+/// Turns the Wi-Fi radio on or off, as `nmcli radio wifi` would.
+fn set_wifi_enabled(conn: &NmConn, enabled: bool) -> Result<(), dbus::Error> {
+    nm_call::<(), _>(conn, NM_PATH, NM_PROPERTIES_IFACE, "Set", (NM_IFACE, "WirelessEnabled", dbus::arg::Variant(enabled)))
+}
+
+// Wi-Fi list -------------------------------------------------------------------------------------------------------------------------------------- //
+// Read once through GetAllAccessPoints, then kept current from NetworkManager's signals on the
+// shared connection; rows are updated in place instead of rebuilding the list.
+
+const NM_IFACE: &str = "org.freedesktop.NetworkManager";
+const NM_DEVICE_IFACE: &str = "org.freedesktop.NetworkManager.Device";
+const NM_WIRELESS_IFACE: &str = "org.freedesktop.NetworkManager.Device.Wireless";
+const NM_AP_IFACE: &str = "org.freedesktop.NetworkManager.AccessPoint";
+const NM_PROPERTIES_IFACE: &str = "org.freedesktop.DBus.Properties";
+const NM_DEVICE_TYPE_WIFI: u32 = 2;
+
+#[derive(Clone, Debug)]
+struct AccessPointInfo {
+    path: String,
+    device: String,
+    ssid: String,
+    bssid: String,
+    strength: u8,
+    // MHz
+    frequency: u32,
+    // kb/s
+    max_bitrate: u32,
+    security: WifiSecurity,
+}
+
+fn wifi_band(frequency: u32) -> &'static str {
+    match frequency {
+        2400..=2500 => "2.4 GHz",
+        4900..=5900 => "5 GHz",
+        5925..=7125 => "6 GHz",
+        _ => "",
+    }
+}
+
+fn wifi_channel(frequency: u32) -> u32 {
+    match frequency {
+        2484 => 14,
+        2400..=2483 => (frequency - 2407) / 5,
+        4900..=5900 => (frequency - 5000) / 5,
+        5925..=7125 => (frequency - 5950) / 5,
+        _ => 0,
+    }
+}
+
+fn wifi_signal_icon(strength: u8) -> &'static str {
+    match strength {
+        80.. => "network-wireless-signal-excellent-symbolic",
+        55..=79 => "network-wireless-signal-good-symbolic",
+        30..=54 => "network-wireless-signal-ok-symbolic",
+        5..=29 => "network-wireless-signal-weak-symbolic",
+        _ => "network-wireless-signal-none-symbolic",
+    }
+}
+
+/// Every access point broadcasting the same SSID with the same security, shown as one row.
+#[derive(Clone, Debug)]
+struct WifiNetwork {
+    ssid: String,
+    security: WifiSecurity,
+    saved: bool,
+    active: bool,
+    // strongest first
+    aps: Vec<AccessPointInfo>,
+}
+
+impl WifiNetwork {
+    fn key(&self) -> String {
+        format!("{}|{}", self.ssid, self.security.label())
+    }
+
+    fn best(&self) -> &AccessPointInfo {
+        &self.aps[0]
+    }
+
+    fn bands(&self) -> String {
+        let mut bands: Vec<&str> = Vec::new();
+        for ap in &self.aps {
+            let band = wifi_band(ap.frequency);
+            if !band.is_empty() && !bands.contains(&band) {
+                bands.push(band);
+            }
+        }
+        bands.sort();
+        bands.join(" / ")
+    }
+}
+
+fn read_access_point(nm: &NmConn, path: &str, device: &str) -> Option<AccessPointInfo> {
+    let (props,) = nm_call::<(dbus::arg::PropMap,), _>(nm, path, NM_PROPERTIES_IFACE, "GetAll", (NM_AP_IFACE,)).ok()?;
+    let u32_prop = |name: &str| dbus::arg::prop_cast::<u32>(&props, name).copied().unwrap_or(0);
+    let ssid = dbus::arg::prop_cast::<Vec<u8>>(&props, "Ssid").map(|s| String::from_utf8_lossy(s).to_string())?;
+    // hidden networks have no SSID to show
+    if ssid.is_empty() {
+        return None;
+    }
+    Some(AccessPointInfo {
+        path: path.to_string(),
+        device: device.to_string(),
+        ssid,
+        bssid: dbus::arg::prop_cast::<String>(&props, "HwAddress").cloned().unwrap_or_default(),
+        strength: dbus::arg::prop_cast::<u8>(&props, "Strength").copied().unwrap_or(0),
+        frequency: u32_prop("Frequency"),
+        max_bitrate: u32_prop("MaxBitrate"),
+        security: WifiSecurity::from_ap_flags(u32_prop("Flags"), u32_prop("WpaFlags"), u32_prop("RsnFlags")),
+    })
+}
+
+fn nm_devices_of_type(nm: &NmConn, device_type: u32) -> Vec<String> {
+    let Ok((devices,)) = nm_call::<(Vec<dbus::Path<'static>>,), _>(nm, NM_PATH, NM_IFACE, "GetDevices", ()) else {
+        return Vec::new();
+    };
+    devices
+        .into_iter()
+        .map(|p| p.to_string())
+        .filter(|p| nm_prop::<u32>(nm, p, NM_DEVICE_IFACE, "DeviceType").ok() == Some(device_type))
+        .collect()
+}
+
+#[derive(Clone)]
+struct WifiPage {
+    nm: NmConn,
+    notif_box: GtkBox,
+    // one BoxedAnyObject(WifiNetwork) per WifiNetwork::key, updated in place from `aps`
+    store: gtk4::gio::ListStore,
+    // `store` in display order: connected, then saved, then by signal
+    sorter: gtk4::CustomSorter,
+    sorted: gtk4::SortListModel,
+    // every list item the factory set up, so changed networks can be redrawn without rebinding
+    list_items: Rc<RefCell<Vec<glib::WeakRef<gtk4::ListItem>>>>,
+    // access point path -> info, for every wifi device
+    aps: Rc<RefCell<HashMap<String, AccessPointInfo>>>,
+    saved: Rc<RefCell<HashSet<String>>>,
+    // WifiNetwork::key of the rows showing their access points
+    expanded: Rc<RefCell<HashSet<String>>>,
+    // wifi device path -> path of the access point it is associated with
+    active_aps: Rc<RefCell<HashMap<String, String>>>,
+    // SSID being activated and how far it got
+    connecting: Rc<RefCell<Option<(String, String)>>>,
+    // SSID the wifi device is broadcasting while the hotspot is on
+    hotspot_ssid: Rc<RefCell<Option<String>>>,
+}
+
+impl WifiPage {
+    fn new(nm: &NmConn, notif_box: &GtkBox) -> Self {
+        let store = gtk4::gio::ListStore::new::<glib::BoxedAnyObject>();
+        let sorter = gtk4::CustomSorter::new(|a, b| {
+            let (Some(a), Some(b)) = (a.downcast_ref::<glib::BoxedAnyObject>(), b.downcast_ref::<glib::BoxedAnyObject>()) else {
+                return gtk4::Ordering::Equal;
+            };
+            let (a, b) = (a.borrow::<WifiNetwork>(), b.borrow::<WifiNetwork>());
+            b.active
+                .cmp(&a.active)
+                .then_with(|| b.saved.cmp(&a.saved))
+                .then_with(|| b.best().strength.cmp(&a.best().strength))
+                .then_with(|| a.ssid.cmp(&b.ssid))
+                .then_with(|| a.key().cmp(&b.key()))
+                .into()
+        });
+        let sorted = gtk4::SortListModel::new(Some(store.clone()), Some(sorter.clone()));
+        WifiPage {
+            nm: nm.clone(),
+            notif_box: notif_box.clone(),
+            store,
+            sorter,
+            sorted,
+            list_items: Rc::new(RefCell::new(Vec::new())),
+            aps: Rc::new(RefCell::new(HashMap::new())),
+            saved: Rc::new(RefCell::new(HashSet::new())),
+            expanded: Rc::new(RefCell::new(HashSet::new())),
+            active_aps: Rc::new(RefCell::new(HashMap::new())),
+            connecting: Rc::new(RefCell::new(None)),
+            hotspot_ssid: Rc::new(RefCell::new(None)),
+        }
+    }
+
+    fn upsert(&self, info: AccessPointInfo) {
+        self.aps.borrow_mut().insert(info.path.clone(), info);
+        self.regroup();
+    }
+
+    fn remove(&self, ap_path: &str) {
+        if self.aps.borrow_mut().remove(ap_path).is_some() {
+            self.regroup();
+        }
+    }
+
+    fn is_active(&self, ap_path: &str) -> bool {
+        self.active_aps.borrow().values().any(|p| p == ap_path)
+    }
+
+    fn update_strength(&self, ap_path: &str, strength: u8) {
+        match self.aps.borrow_mut().get_mut(ap_path) {
+            Some(info) => info.strength = strength,
+            None => return,
+        }
+        self.regroup();
+    }
+
+    /// Groups the access points into networks. Each network keeps its object, so the list view
+    /// keeps its row (focus, hover, expansion) while `sorted` moves it.
+    fn regroup(&self) {
+        let saved = self.saved.borrow();
+        let hotspot_ssid = self.hotspot_ssid.borrow();
+        let mut networks: Vec<WifiNetwork> = Vec::new();
+        for ap in self.aps.borrow().values() {
+            if hotspot_ssid.as_deref() == Some(ap.ssid.as_str()) {
+                continue;
+            }
+            match networks.iter_mut().find(|n| n.ssid == ap.ssid && n.security == ap.security) {
+                Some(network) => network.aps.push(ap.clone()),
+                None => networks.push(WifiNetwork {
+                    ssid: ap.ssid.clone(),
+                    security: ap.security,
+                    saved: saved.contains(&ap.ssid),
+                    active: false,
+                    aps: vec![ap.clone()],
+                }),
+            }
+        }
+        for network in &mut networks {
+            network.aps.sort_by(|a, b| b.strength.cmp(&a.strength).then_with(|| a.bssid.cmp(&b.bssid)));
+            network.active = network.aps.iter().any(|ap| self.is_active(&ap.path));
+        }
+        drop((saved, hotspot_ssid));
+
+        let mut by_key: HashMap<String, WifiNetwork> = networks.into_iter().map(|n| (n.key(), n)).collect();
+        for i in (0..self.store.n_items()).rev() {
+            let Some(obj) = self.store.item(i).and_downcast::<glib::BoxedAnyObject>() else {
+                continue;
+            };
+            let key = obj.borrow::<WifiNetwork>().key();
+            match by_key.remove(&key) {
+                Some(network) => *obj.borrow_mut::<WifiNetwork>() = network,
+                None => self.store.remove(i),
+            }
+        }
+        let mut added: Vec<WifiNetwork> = by_key.into_values().collect();
+        added.sort_by_key(|n| n.key());
+        for network in added {
+            self.store.append(&glib::BoxedAnyObject::new(network));
+        }
+        self.sorter.changed(gtk4::SorterChange::Different);
+        self.refresh_rows();
+    }
+
+    /// Redraws the rows on screen from their (updated) networks.
+    fn refresh_rows(&self) {
+        self.list_items.borrow_mut().retain(|weak| weak.upgrade().is_some());
+        let list_items: Vec<gtk4::ListItem> = self.list_items.borrow().iter().filter_map(|weak| weak.upgrade()).collect();
+        for list_item in list_items {
+            fill_wifi_row(self, &list_item);
+        }
+    }
+
+    fn read_saved(&self) {
+        *self.saved.borrow_mut() = nm_saved_wifi_ssids(&self.nm);
+        self.regroup();
+    }
+
+    fn read_active_aps(&self) {
+        let devices = nm_devices_of_type(&self.nm, NM_DEVICE_TYPE_WIFI);
+        let active: HashMap<String, String> = devices
+            .into_iter()
+            .filter_map(|dev| {
+                let ap = nm_prop::<dbus::Path<'static>>(&self.nm, &dev, NM_WIRELESS_IFACE, "ActiveAccessPoint").ok()?;
+                Some((dev, ap.to_string()))
+            })
+            .collect();
+        *self.active_aps.borrow_mut() = active;
+        self.regroup();
+    }
+
+    fn reload(&self) {
+        self.aps.borrow_mut().clear();
+        for device in nm_devices_of_type(&self.nm, NM_DEVICE_TYPE_WIFI) {
+            let Ok((aps,)) = nm_call::<(Vec<dbus::Path<'static>>,), _>(&self.nm, &device, NM_WIRELESS_IFACE, "GetAllAccessPoints", ()) else {
+                continue;
+            };
+            for ap in aps {
+                if let Some(info) = read_access_point(&self.nm, &ap, &device) {
+                    self.aps.borrow_mut().insert(info.path.clone(), info);
+                }
+            }
+        }
+        *self.saved.borrow_mut() = nm_saved_wifi_ssids(&self.nm);
+        self.read_active_aps();
+    }
+}
+
+fn watch_wifi_signals(page: &WifiPage) {
+    use dbus::message::MatchRule;
+    let nm = &page.nm;
+
+    let page_clone = page.clone();
+    let added = MatchRule::new_signal(NM_WIRELESS_IFACE, "AccessPointAdded").with_sender(NM_BUS);
+    let _ = nm.add_match(added, move |(ap,): (dbus::Path<'static>,), _, msg| {
+        let device = msg.path().map(|p| p.to_string()).unwrap_or_default();
+        if let Some(info) = read_access_point(&page_clone.nm, &ap, &device) {
+            page_clone.upsert(info);
+        }
+        true
+    });
+
+    let page_clone = page.clone();
+    let removed = MatchRule::new_signal(NM_WIRELESS_IFACE, "AccessPointRemoved").with_sender(NM_BUS);
+    let _ = nm.add_match(removed, move |(ap,): (dbus::Path<'static>,), _, _| {
+        page_clone.remove(&ap);
+        true
+    });
+
+    let page_clone = page.clone();
+    let changed = MatchRule::new_signal(NM_PROPERTIES_IFACE, "PropertiesChanged")
+        .with_sender(NM_BUS)
+        .with_namespaced_path(NM_PATH);
+    let _ = nm.add_match(changed, move |(iface, props, _): (String, dbus::arg::PropMap, Vec<String>), _, msg| {
+        let path = msg.path().map(|p| p.to_string()).unwrap_or_default();
+        match iface.as_str() {
+            NM_AP_IFACE => {
+                if let Some(strength) = dbus::arg::prop_cast::<u8>(&props, "Strength") {
+                    page_clone.update_strength(&path, *strength);
+                }
+            }
+            NM_WIRELESS_IFACE if props.contains_key("ActiveAccessPoint") => page_clone.read_active_aps(),
+            _ => {}
+        }
+        true
+    });
+
+    let page_clone = page.clone();
+    let state = MatchRule::new_signal(NM_IFACE, "StateChanged").with_sender(NM_BUS).with_path(NM_PATH);
+    let _ = nm.add_match(state, move |(_,): (u32,), _, _| {
+        page_clone.read_active_aps();
+        true
+    });
+
+    // saved badges and ordering
+    for member in ["NewConnection", "ConnectionRemoved"] {
+        let page_clone = page.clone();
+        let rule = MatchRule::new_signal(NM_SETTINGS_IFACE, member)
+            .with_sender(NM_BUS)
+            .with_path(format!("{}/Settings", NM_PATH));
+        let _ = nm.add_match(rule, move |(_,): (dbus::Path<'static>,), _, _| {
+            page_clone.read_saved();
+            true
+        });
+    }
+
+    // a wifi adapter plugged in or removed
+    for member in ["DeviceAdded", "DeviceRemoved"] {
+        let page_clone = page.clone();
+        let rule = MatchRule::new_signal(NM_IFACE, member).with_sender(NM_BUS).with_path(NM_PATH);
+        let _ = nm.add_match(rule, move |(_,): (dbus::Path<'static>,), _, _| {
+            page_clone.reload();
+            true
+        });
+    }
+}
+
+// Wi-Fi activation -------------------------------------------------------------------------------------------------------------------------------- //
+// ActivateConnection / AddAndActivateConnection return as soon as NetworkManager has started;
+// progress and the outcome come from the device's and the active connection's StateChanged.
+
+const NM_ACTIVE_IFACE: &str = "org.freedesktop.NetworkManager.Connection.Active";
+
+fn device_state_text(state: u32) -> Option<&'static str> {
+    match state {
+        40 => Some("preparing"),
+        50 => Some("configuring"),
+        60 => Some("authenticating"),
+        70 => Some("getting an IP address"),
+        80 => Some("checking connectivity"),
+        90 => Some("starting secondary connections"),
+        100 => Some("connected"),
+        _ => None,
+    }
+}
+
+/// NMDeviceStateReason values worth telling the user about.
+fn device_failure_reason(reason: u32) -> Option<&'static str> {
+    match reason {
+        4 => Some("the configuration failed"),
+        5 | 6 => Some("no IP configuration available"),
+        7 => Some("no password was given"),
+        8 | 9 => Some("wrong password"),
+        10 => Some("authentication failed"),
+        11 => Some("timed out while authenticating"),
+        15..=17 => Some("no DHCP answer"),
+        53 => Some("the network is out of range"),
+        _ => None,
+    }
+}
+
+/// NMActiveConnectionStateReason, used when the device gave no better reason.
+fn active_failure_reason(reason: u32) -> &'static str {
+    match reason {
+        2 => "disconnected by the user",
+        3 => "the device disconnected",
+        5 => "the IP configuration was invalid",
+        6 => "timed out",
+        9 => "no password was given",
+        10 => "login failed",
+        11 => "the connection was removed",
+        14 => "the device was removed",
+        _ => "unknown reason",
+    }
+}
+
+fn wifi_security_settings(security: WifiSecurity, password: Option<String>) -> NmSettings {
+    let mut settings: NmSettings = HashMap::new();
+    let key_mgmt = match security {
+        WifiSecurity::Psk => "wpa-psk",
+        WifiSecurity::Sae => "sae",
+        WifiSecurity::Wep => "none",
+        WifiSecurity::Owe => "owe",
+        WifiSecurity::Open | WifiSecurity::Enterprise => return settings,
+    };
+    let mut wsec = dbus::arg::PropMap::new();
+    wsec.insert("key-mgmt".to_string(), dbus::arg::Variant(Box::new(key_mgmt.to_string())));
+    if let Some(password) = password {
+        if security == WifiSecurity::Wep {
+            wsec.insert("wep-key-type".to_string(), dbus::arg::Variant(Box::new(wep_key_type(&password))));
+            wsec.insert("wep-key0".to_string(), dbus::arg::Variant(Box::new(password)));
+        } else {
+            wsec.insert("psk".to_string(), dbus::arg::Variant(Box::new(password)));
+        }
+    }
+    settings.insert("802-11-wireless-security".to_string(), wsec);
+    settings
+}
+
+impl WifiPage {
+    fn set_connecting(&self, status: Option<(&str, &str)>) {
+        *self.connecting.borrow_mut() = status.map(|(ssid, text)| (ssid.to_string(), text.to_string()));
+        self.refresh_rows();
+    }
+
+    /// Connects to the network, asking for a password first when it is secured and not saved.
+    fn connect(&self, info: &AccessPointInfo, parent: &impl IsA<gtk4::Widget>) {
+        if self.connecting.borrow().as_ref().is_some_and(|(ssid, _)| *ssid == info.ssid) {
+            return;
+        }
+        if self.hotspot_ssid.borrow().is_some() {
+            show_notification(&self.notif_box, &format!("the hotspot is using the Wi-Fi adapter, turn it off to join {}", info.ssid));
+            return;
+        }
+        let saved = nm_saved_wifi_ssids(&self.nm).contains(&info.ssid);
+        if saved || !info.security.needs_password() && info.security != WifiSecurity::Enterprise {
+            self.activate(info.clone(), None);
+        } else if info.security == WifiSecurity::Enterprise {
+            let parent = parent.root().and_downcast::<gtk4::Window>();
+            open_join_network_dialog(self, parent.as_ref(), Some(&info.ssid), info.security);
+        } else {
+            let parent = parent.root().and_downcast::<gtk4::Window>();
+            let page = self.clone();
+            let info_clone = info.clone();
+            ask_wifi_password(parent.as_ref(), &info.ssid, info.security, false, move |password| {
+                if password.is_some() {
+                    page.activate(info_clone, password);
+                }
+            });
+        }
+    }
+
+    fn activate(&self, info: AccessPointInfo, password: Option<String>) {
+        let saved = nm_saved_connections(&self.nm)
+            .into_iter()
+            .find(|(_, settings)| settings_ssid(settings).as_deref() == Some(info.ssid.as_str()))
+            .map(|(path, _)| path);
+        let device = dbus::Path::from(info.device.clone());
+        let ap = dbus::Path::from(info.path.clone());
+        let result = match saved {
+            Some(connection) => nm_call::<(dbus::Path<'static>,), _>(&self.nm, NM_PATH, NM_IFACE, "ActivateConnection", (connection, device, ap))
+                .map(|(active,)| active),
+            None => {
+                let settings = wifi_security_settings(info.security, password);
+                nm_call::<(dbus::Path<'static>, dbus::Path<'static>), _>(&self.nm, NM_PATH, NM_IFACE, "AddAndActivateConnection", (settings, device, ap))
+                    .map(|(_, active)| active)
+            }
+        };
+        match result {
+            Ok(active) => {
+                self.set_connecting(Some((&info.ssid, "starting")));
+                self.track_activation(&info.ssid, &info.device, &active);
+            }
+            Err(e) => show_notification(
+                &self.notif_box,
+                &format!("could not connect to {}: {}", info.ssid, e.message().unwrap_or("NetworkManager refused")),
+            ),
+        }
+    }
+
+    /// Creates and activates a profile for a network that may not be in the scan results
+    /// (hidden, or out of sight so far); false when NetworkManager refused it.
+    fn join_new_network(&self, ssid: &str, settings: NmSettings) -> bool {
+        let Some(device) = nm_devices_of_type(&self.nm, NM_DEVICE_TYPE_WIFI).into_iter().next() else {
+            show_notification(&self.notif_box, "no Wi-Fi adapter found");
+            return false;
+        };
+        let result = nm_call::<(dbus::Path<'static>, dbus::Path<'static>), _>(
+            &self.nm,
+            NM_PATH,
+            NM_IFACE,
+            "AddAndActivateConnection",
+            (settings, dbus::Path::from(device.clone()), dbus::Path::from("/")),
+        );
+        match result {
+            Ok((_, active)) => {
+                self.set_connecting(Some((ssid, "starting")));
+                self.track_activation(ssid, &device, &active);
+                true
+            }
+            Err(e) => {
+                show_notification(&self.notif_box, &format!("could not join {}: {}", ssid, e.message().unwrap_or("NetworkManager refused")));
+                false
+            }
+        }
+    }
+
+    fn track_activation(&self, ssid: &str, device: &str, active: &str) {
+        use dbus::message::MatchRule;
+        let finished = Rc::new(std::cell::Cell::new(false));
+        let device_reason = Rc::new(std::cell::Cell::new(0u32));
+        let tokens = Rc::new(RefCell::new(Vec::new()));
+
+        let page = self.clone();
+        let (ssid_clone, finished_clone, device_reason_clone) = (ssid.to_string(), finished.clone(), device_reason.clone());
+        let device_rule = MatchRule::new_signal(NM_DEVICE_IFACE, "StateChanged")
+            .with_sender(NM_BUS)
+            .with_path(device.to_string());
+        let device_token = self.nm.add_match(device_rule, move |(state, _, reason): (u32, u32, u32), _, _| {
+            if finished_clone.get() {
+                return false;
+            }
+            if let Some(text) = device_state_text(state) {
+                page.set_connecting(Some((&ssid_clone, text)));
+            }
+            // 120 = failed, the active connection reports deactivated right after
+            if state == 120 {
+                device_reason_clone.set(reason);
+            }
+            true
+        });
+
+        let finish = {
+            let page = self.clone();
+            let (ssid, finished, tokens) = (ssid.to_string(), finished.clone(), tokens.clone());
+            move |result: Result<(), String>| {
+                if finished.replace(true) {
+                    return;
+                }
+                page.set_connecting(None);
+                match result {
+                    Ok(()) => show_notification(&page.notif_box, &format!("connected to {}", ssid)),
+                    Err(reason) => show_notification(&page.notif_box, &format!("could not connect to {}: {}", ssid, reason)),
+                }
+                // matches can't be removed from inside their own callback
+                let nm = page.nm.clone();
+                let tokens = tokens.clone();
+                glib::idle_add_local_once(move || {
+                    for token in tokens.borrow_mut().drain(..) {
+                        let _ = nm.remove_match(token);
+                    }
+                });
+            }
+        };
+        let outcome = move |state: u32, reason: u32| match state {
+            2 => finish(Ok(())),
+            4 => finish(Err(device_failure_reason(device_reason.get())
+                .unwrap_or_else(|| active_failure_reason(reason))
+                .to_string())),
+            _ => {}
+        };
+
+        let outcome_clone = outcome.clone();
+        let active_rule = MatchRule::new_signal(NM_ACTIVE_IFACE, "StateChanged")
+            .with_sender(NM_BUS)
+            .with_path(active.to_string());
+        let active_token = self.nm.add_match(active_rule, move |(state, reason): (u32, u32), _, _| {
+            outcome_clone(state, reason);
+            true
+        });
+        tokens.borrow_mut().extend(device_token.into_iter().chain(active_token));
+
+        // it may have finished before the matches were in place
+        if let Ok(state) = nm_prop::<u32>(&self.nm, active, NM_ACTIVE_IFACE, "State") {
+            outcome(state, 0);
+        }
+    }
+}
+
+// Join other network ------------------------------------------------------------------------------------------------------------------------------ //
+// Hidden and 802.1X networks: the whole profile is built here and handed to
+// AddAndActivateConnection with "/" as the access point, so NetworkManager scans for it.
+
+const JOIN_SECURITY_OPTIONS: [&str; 4] = ["None", "WPA/WPA2 Personal", "WPA3 Personal (SAE)", "Enterprise (802.1X)"];
+const EAP_METHODS: [&str; 3] = ["peap", "ttls", "tls"];
+const EAP_LABELS: [&str; 3] = ["PEAP", "TTLS", "TLS"];
+const PHASE2_METHODS: [&str; 3] = ["mschapv2", "pap", "gtc"];
+const PHASE2_LABELS: [&str; 3] = ["MSCHAPv2", "PAP", "GTC"];
+
+/// NetworkManager takes certificate paths as a NUL terminated "file://" byte string.
+/// Whether a certificate file is a PKCS#12 bundle, which carries its private key with it.
+fn is_pkcs12(path: &Path) -> bool {
+    path.extension()
+        .map(|e| e.eq_ignore_ascii_case("p12") || e.eq_ignore_ascii_case("pfx"))
+        .unwrap_or(false)
+}
+
+fn nm_cert_path(path: &Path) -> Vec<u8> {
+    let mut bytes = format!("file://{}", path.display()).into_bytes();
+    bytes.push(0);
+    bytes
+}
+
+/// A button that picks a file and shows its name; the path is read from the returned cell.
+fn cert_file_button(title: &'static str, dialog_window: &gtk4::Window) -> (Button, Rc<RefCell<Option<PathBuf>>>) {
+    let button = Button::with_label("(none)");
+    let chosen: Rc<RefCell<Option<PathBuf>>> = Rc::new(RefCell::new(None));
+    let chosen_clone = chosen.clone();
+    let window = dialog_window.clone();
+    button.connect_clicked(move |button| {
+        let dialog = FileChooserDialog::new(
+            Some(title),
+            Some(&window),
+            FileChooserAction::Open,
+            &[("_Cancel", ResponseType::Cancel), ("_Select", ResponseType::Accept)],
+        );
+        dialog.set_css_classes(&["wall-dialog"]);
+        dialog.set_size_request(400, 800);
+        add_class_recursive(dialog.upcast_ref(), "wall-dialog");
+        let filter = gtk4::FileFilter::new();
+        filter.set_name(Some("Certificates and keys"));
+        for pattern in ["*.pem", "*.crt", "*.cer", "*.der", "*.key", "*.p12", "*.pfx"] {
+            filter.add_pattern(pattern);
+        }
+        dialog.add_filter(&filter);
+
+        let (chosen, button) = (chosen_clone.clone(), button.clone());
+        dialog.connect_response(move |dialog, response| {
+            let path = dialog.file().and_then(|f| f.path());
+            dialog.close();
+            if let (ResponseType::Accept, Some(path)) = (response, path) {
+                // store the path first, the label change is what the dialog revalidates on
+                let label = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+                *chosen.borrow_mut() = Some(path);
+                button.set_label(&label);
+            }
+        });
+        dialog.show();
+    });
+    (button, chosen)
+}
+
+fn grid_row(grid: &gtk4::Grid, row: i32, label: &str, widget: &impl IsA<gtk4::Widget>) -> Label {
+    let label = Label::new(Some(label));
+    label.set_halign(gtk4::Align::Start);
+    grid.attach(&label, 0, row, 1, 1);
+    grid.attach(widget, 1, row, 1, 1);
+    label
+}
+
+fn open_join_network_dialog(page: &WifiPage, parent: Option<&gtk4::Window>, ssid: Option<&str>, security: WifiSecurity) {
+    let window = gtk4::Window::builder().title("Join other network").modal(true).resizable(false).build();
+    window.set_transient_for(parent);
+
+    let dialog_box = GtkBox::new(Orientation::Vertical, 10);
+    dialog_box.set_margin_top(15);
+    dialog_box.set_margin_bottom(15);
+    dialog_box.set_margin_start(15);
+    dialog_box.set_margin_end(15);
+    let grid = gtk4::Grid::builder().row_spacing(8).column_spacing(20).build();
+
+    let ssid_entry = gtk4::Entry::builder().placeholder_text("Network name").hexpand(true).build();
+    ssid_entry.set_text(ssid.unwrap_or_default());
+    let security_dropdown = gtk4::DropDown::from_strings(&JOIN_SECURITY_OPTIONS);
+    security_dropdown.set_selected(match security {
+        WifiSecurity::Psk => 1,
+        WifiSecurity::Sae => 2,
+        WifiSecurity::Enterprise => 3,
+        _ => 0,
+    });
+    let password = gtk4::Entry::builder().visibility(false).input_purpose(gtk4::InputPurpose::Password).build();
+    let eap = gtk4::DropDown::from_strings(&EAP_LABELS);
+    let phase2 = gtk4::DropDown::from_strings(&PHASE2_LABELS);
+    let identity = gtk4::Entry::new();
+    let anonymous_identity = gtk4::Entry::builder().placeholder_text("optional").build();
+    let (ca_cert, ca_cert_path) = cert_file_button("CA certificate", &window);
+    let (client_cert, client_cert_path) = cert_file_button("Client certificate", &window);
+    let (private_key, private_key_path) = cert_file_button("Private key", &window);
+    let key_password = gtk4::Entry::builder().visibility(false).input_purpose(gtk4::InputPurpose::Password).build();
+
+    grid_row(&grid, 0, "Network name", &ssid_entry);
+    grid_row(&grid, 1, "Security", &security_dropdown);
+    let eap_rows = [
+        (grid_row(&grid, 2, "Authentication", &eap), eap.clone().upcast::<gtk4::Widget>()),
+        (grid_row(&grid, 3, "Inner authentication", &phase2), phase2.clone().upcast()),
+        (grid_row(&grid, 4, "Identity", &identity), identity.clone().upcast()),
+        (grid_row(&grid, 5, "Anonymous identity", &anonymous_identity), anonymous_identity.clone().upcast()),
+        (grid_row(&grid, 6, "CA certificate", &ca_cert), ca_cert.clone().upcast()),
+    ];
+    let password_row = (grid_row(&grid, 7, "Password", &password), password.clone().upcast::<gtk4::Widget>());
+    let tls_rows = [
+        (grid_row(&grid, 8, "Client certificate", &client_cert), client_cert.clone().upcast::<gtk4::Widget>()),
+        (grid_row(&grid, 9, "Private key", &private_key), private_key.clone().upcast()),
+        (grid_row(&grid, 10, "Private key password", &key_password), key_password.clone().upcast()),
+    ];
+    let phase2_row = (eap_rows[1].0.clone(), eap_rows[1].1.clone());
+
+    let buttons = GtkBox::new(Orientation::Horizontal, 5);
+    buttons.set_halign(gtk4::Align::End);
+    let cancel = Button::with_label("Cancel");
+    let connect = Button::with_label("Connect");
+    buttons.append(&cancel);
+    buttons.append(&connect);
+    dialog_box.append(&grid);
+    dialog_box.append(&buttons);
+    window.set_child(Some(&dialog_box));
+    window.set_default_widget(Some(&connect));
+
+    // show the fields that belong to the chosen security and EAP method
+    let update = {
+        let (security_dropdown, eap, connect) = (security_dropdown.clone(), eap.clone(), connect.clone());
+        let (ssid_entry, password, identity) = (ssid_entry.clone(), password.clone(), identity.clone());
+        let (client_cert_path, private_key_path) = (client_cert_path.clone(), private_key_path.clone());
+        move || {
+            let enterprise = security_dropdown.selected() == 3;
+            let tls = enterprise && eap.selected() == 2;
+            let personal = matches!(security_dropdown.selected(), 1 | 2);
+            for (label, widget) in &eap_rows {
+                label.set_visible(enterprise);
+                widget.set_visible(enterprise);
+            }
+            phase2_row.0.set_visible(enterprise && !tls);
+            phase2_row.1.set_visible(enterprise && !tls);
+            password_row.0.set_visible(personal || enterprise && !tls);
+            password_row.1.set_visible(personal || enterprise && !tls);
+            for (label, widget) in &tls_rows {
+                label.set_visible(tls);
+                widget.set_visible(tls);
+            }
+            let valid = !ssid_entry.text().is_empty()
+                && ssid_entry.text().len() <= 32
+                && match security_dropdown.selected() {
+                    1 => WifiSecurity::Psk.is_valid_password(&password.text()),
+                    2 => WifiSecurity::Sae.is_valid_password(&password.text()),
+                    3 if tls => {
+                        let client_cert = client_cert_path.borrow();
+                        !identity.text().is_empty()
+                            && client_cert.as_deref().is_some_and(|cert| is_pkcs12(cert) || private_key_path.borrow().is_some())
+                    }
+                    3 => !identity.text().is_empty() && !password.text().is_empty(),
+                    _ => true,
+                };
+            connect.set_sensitive(valid);
+        }
+    };
+    let update = Rc::new(update);
+    update();
+    for dropdown in [&security_dropdown, &eap] {
+        let update = update.clone();
+        dropdown.connect_selected_notify(move |_| update());
+    }
+    for entry in [&ssid_entry, &password, &identity] {
+        let update = update.clone();
+        entry.connect_changed(move |_| update());
+    }
+    for button in [&client_cert, &private_key] {
+        let update = update.clone();
+        button.connect_label_notify(move |_| update());
+    }
+
+    let window_clone = window.clone();
+    cancel.connect_clicked(move |_| window_clone.close());
+
+    // only a network typed in by hand is hidden; probing for a visible one would just leak its name
+    let hidden = ssid.is_none();
+    let page = page.clone();
+    let window_clone = window.clone();
+    connect.connect_clicked(move |_| {
+        let ssid = ssid_entry.text().to_string();
+        let mut settings: NmSettings = HashMap::new();
+        settings_set(&mut settings, "connection", "id", ssid.clone());
+        settings_set(&mut settings, "connection", "type", "802-11-wireless".to_string());
+        settings_set(&mut settings, "802-11-wireless", "ssid", ssid.clone().into_bytes());
+        settings_set(&mut settings, "802-11-wireless", "mode", "infrastructure".to_string());
+        if hidden {
+            settings_set(&mut settings, "802-11-wireless", "hidden", true);
+        }
+        let wsec = "802-11-wireless-security";
+        match security_dropdown.selected() {
+            1 | 2 => {
+                let key_mgmt = if security_dropdown.selected() == 1 { "wpa-psk" } else { "sae" };
+                settings_set(&mut settings, wsec, "key-mgmt", key_mgmt.to_string());
+                settings_set(&mut settings, wsec, "psk", password.text().to_string());
+            }
+            3 => {
+                settings_set(&mut settings, wsec, "key-mgmt", "wpa-eap".to_string());
+                let method = dropdown_value(&eap, &EAP_METHODS);
+                settings_set(&mut settings, "802-1x", "eap", vec![method.to_string()]);
+                settings_set(&mut settings, "802-1x", "identity", identity.text().to_string());
+                if !anonymous_identity.text().is_empty() {
+                    settings_set(&mut settings, "802-1x", "anonymous-identity", anonymous_identity.text().to_string());
+                }
+                if let Some(path) = &*ca_cert_path.borrow() {
+                    settings_set(&mut settings, "802-1x", "ca-cert", nm_cert_path(path));
+                }
+                if method == "tls" {
+                    if let Some(path) = &*client_cert_path.borrow() {
+                        settings_set(&mut settings, "802-1x", "client-cert", nm_cert_path(path));
+                    }
+                    // a PKCS#12 file holds the key as well
+                    if let Some(path) = private_key_path.borrow().as_ref().or(client_cert_path.borrow().as_ref()) {
+                        settings_set(&mut settings, "802-1x", "private-key", nm_cert_path(path));
+                    }
+                    if !key_password.text().is_empty() {
+                        settings_set(&mut settings, "802-1x", "private-key-password", key_password.text().to_string());
+                    }
+                } else {
+                    settings_set(&mut settings, "802-1x", "phase2-auth", dropdown_value(&phase2, &PHASE2_METHODS).to_string());
+                    settings_set(&mut settings, "802-1x", "password", password.text().to_string());
+                }
+            }
+            _ => {}
+        }
+
+        if page.join_new_network(&ssid, settings) {
+            window_clone.close();
+        }
+    });
+
+    window.present();
+}
+
+/// The widgets of one network row, kept on its list item as "wifi-row" for the bind handler.
+#[derive(Clone)]
+struct WifiRow {
+    root: GtkBox,
+    button: Button,
+    signal: gtk4::Image,
+    name: Label,
+    saved: Label,
+    band: Label,
+    lock: gtk4::Image,
+    spinner: gtk4::Spinner,
+    status: Label,
+    share: Button,
+    expand: gtk4::ToggleButton,
+    revealer: gtk4::Revealer,
+    details: Label,
+}
+
+impl WifiRow {
+    fn new() -> Self {
+        let root = GtkBox::new(Orientation::Vertical, 0);
+        root.set_css_classes(&["network_row"]);
+        let top = GtkBox::new(Orientation::Horizontal, 0);
+        top.set_css_classes(&["network_row"]);
+
+        let button = Button::new();
+        button.set_css_classes(&["network_label"]);
+        button.set_hexpand(true);
+        let content = GtkBox::new(Orientation::Horizontal, 10);
+        content.set_css_classes(&["network_row"]);
+        let signal = gtk4::Image::new();
+        let name = Label::new(None);
+        name.set_hexpand(true);
+        name.set_halign(gtk4::Align::Start);
+        let saved = Label::new(Some("saved"));
+        saved.set_css_classes(&["network_badge"]);
+        let band = Label::new(None);
+        band.set_css_classes(&["network_badge"]);
+        let lock = gtk4::Image::from_icon_name("network-wireless-encrypted-symbolic");
+        let spinner = gtk4::Spinner::new();
+        let status = Label::new(None);
+        for widget in [
+            signal.upcast_ref::<gtk4::Widget>(),
+            name.upcast_ref(),
+            saved.upcast_ref(),
+            band.upcast_ref(),
+            lock.upcast_ref(),
+            spinner.upcast_ref(),
+            status.upcast_ref(),
+        ] {
+            content.append(widget);
+        }
+        button.set_child(Some(&content));
+
+        let share = Button::from_icon_name("emblem-shared-symbolic");
+        share.set_tooltip_text(Some("Share as QR code"));
+        let expand = gtk4::ToggleButton::new();
+        expand.set_icon_name("pan-down-symbolic");
+        expand.set_tooltip_text(Some("Access points"));
+
+        top.append(&button);
+        top.append(&share);
+        top.append(&expand);
+
+        let details = Label::new(None);
+        details.set_halign(gtk4::Align::Start);
+        details.set_css_classes(&["network_details"]);
+        let revealer = gtk4::Revealer::new();
+        revealer.set_child(Some(&details));
+
+        root.append(&top);
+        root.append(&revealer);
+
+        WifiRow { root, button, signal, name, saved, band, lock, spinner, status, share, expand, revealer, details }
+    }
+}
+
+fn access_point_details(page: &WifiPage, network: &WifiNetwork) -> String {
+    network
+        .aps
+        .iter()
+        .map(|ap| {
+            let mut line = format!(
+                "{}   {} ch {}   {}%   {} Mb/s",
+                ap.bssid,
+                wifi_band(ap.frequency),
+                wifi_channel(ap.frequency),
+                ap.strength,
+                ap.max_bitrate / 1000
+            );
+            if page.is_active(&ap.path) {
+                line.push_str("   connected");
+            }
+            line
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn build_wifi_list(page: &WifiPage) -> gtk4::ListView {
+    let factory = gtk4::SignalListItemFactory::new();
+
+    let page_clone = page.clone();
+    factory.connect_setup(move |_, item| {
+        let Some(list_item) = item.downcast_ref::<gtk4::ListItem>() else {
+            return;
+        };
+        let row = WifiRow::new();
+        list_item.set_child(Some(&row.root));
+        page_clone.list_items.borrow_mut().push(list_item.downgrade());
+
+        // the row is recycled, so look up what it shows at click time
+        let list_item_weak = list_item.downgrade();
+        let page_click = page_clone.clone();
+        row.button.connect_clicked(move |button| {
+            let Some(obj) = list_item_weak.upgrade().and_then(|li| li.item()).and_downcast::<glib::BoxedAnyObject>() else {
+                return;
+            };
+            let best = obj.borrow::<WifiNetwork>().best().clone();
+            page_click.connect(&best, button);
+        });
+
+        let list_item_weak = list_item.downgrade();
+        let page_share = page_clone.clone();
+        row.share.connect_clicked(move |share| {
+            let Some(obj) = list_item_weak.upgrade().and_then(|li| li.item()).and_downcast::<glib::BoxedAnyObject>() else {
+                return;
+            };
+            let network = obj.borrow::<WifiNetwork>().clone();
+            share_wifi_network(&page_share, &network, share);
+        });
+
+        let list_item_weak = list_item.downgrade();
+        let page_clone = page_clone.clone();
+        let revealer = row.revealer.clone();
+        row.expand.connect_toggled(move |expand| {
+            revealer.set_reveal_child(expand.is_active());
+            let Some(obj) = list_item_weak.upgrade().and_then(|li| li.item()).and_downcast::<glib::BoxedAnyObject>() else {
+                return;
+            };
+            let key = obj.borrow::<WifiNetwork>().key();
+            if expand.is_active() {
+                page_clone.expanded.borrow_mut().insert(key);
+            } else {
+                page_clone.expanded.borrow_mut().remove(&key);
+            }
+        });
+        // SAFETY: "wifi-row" is only ever set and read as a WifiRow
+        unsafe { list_item.set_data("wifi-row", row) };
+    });
+
+    let page_clone = page.clone();
+    factory.connect_bind(move |_, item| {
+        if let Some(list_item) = item.downcast_ref::<gtk4::ListItem>() {
+            fill_wifi_row(&page_clone, list_item);
+        }
+    });
+
+    let selection = gtk4::NoSelection::new(Some(page.sorted.clone()));
+    let list = gtk4::ListView::new(Some(selection), Some(factory));
+    list.set_css_classes(&["network_list"]);
+    list
+}
+
+/// Shows the list item's network in its row; a no-op for items not bound to a network.
+fn fill_wifi_row(page: &WifiPage, list_item: &gtk4::ListItem) {
+    // SAFETY: "wifi-row" is only ever set and read as a WifiRow
+    let row = unsafe { list_item.data::<WifiRow>("wifi-row").map(|row| row.as_ref().clone()) };
+    let (Some(row), Some(obj)) = (row, list_item.item().and_downcast::<glib::BoxedAnyObject>()) else {
+        return;
+    };
+    let network = obj.borrow::<WifiNetwork>().clone();
+    let best = network.best();
+
+    row.signal.set_icon_name(Some(wifi_signal_icon(best.strength)));
+    row.signal.set_tooltip_text(Some(&format!("{}%", best.strength)));
+    row.name.set_text(&network.ssid);
+    row.saved.set_visible(network.saved);
+    row.band.set_text(&network.bands());
+    row.band.set_visible(!row.band.text().is_empty());
+    row.lock.set_visible(network.security != WifiSecurity::Open);
+    row.lock.set_tooltip_text(Some(network.security.label()));
+
+    let connecting = page.connecting.borrow().clone().filter(|(ssid, _)| *ssid == network.ssid);
+    row.spinner.set_visible(connecting.is_some());
+    row.spinner.set_spinning(connecting.is_some());
+    row.status.set_text(connecting.as_ref().map(|(_, text)| text.as_str()).unwrap_or(""));
+
+    row.share.set_visible(network.active && network.security != WifiSecurity::Enterprise);
+    row.details.set_text(&access_point_details(page, &network));
+    row.expand.set_tooltip_text(Some(&format!("{} access point(s)", network.aps.len())));
+    row.expand.set_active(page.expanded.borrow().contains(&network.key()));
+
+    // Highlight the already-connected network
+    if network.active {
+        row.button.add_css_class("connected");
+    } else {
+        row.button.remove_css_class("connected");
+    }
+}
+
+// Saved connections ------------------------------------------------------------------------------------------------------------------------------- //
+// Profiles come from Settings.ListConnections and are written back whole with Update, with the
+// secrets fetched first so saving doesn't forget a stored password.
+
+const METERED_OPTIONS: [&str; 3] = ["automatic", "yes", "no"];
+// "custom" stands for a literal address typed next to the dropdown
+const MAC_OPTIONS: [&str; 6] = ["default", "permanent", "preserve", "random", "stable", "custom"];
+const IPV4_METHODS: [&str; 5] = ["auto", "manual", "link-local", "shared", "disabled"];
+const IPV6_METHODS: [&str; 7] = ["auto", "dhcp", "manual", "link-local", "shared", "ignore", "disabled"];
+
+fn settings_bool(settings: &NmSettings, group: &str, key: &str) -> Option<bool> {
+    settings.get(group).and_then(|g| dbus::arg::prop_cast::<bool>(g, key)).copied()
+}
+
+fn settings_i32(settings: &NmSettings, group: &str, key: &str) -> Option<i32> {
+    settings.get(group).and_then(|g| dbus::arg::prop_cast::<i32>(g, key)).copied()
+}
+
+fn settings_set<T: dbus::arg::RefArg + 'static>(settings: &mut NmSettings, group: &str, key: &str, value: T) {
+    settings
+        .entry(group.to_string())
+        .or_default()
+        .insert(key.to_string(), dbus::arg::Variant(Box::new(value)));
+}
+
+fn settings_remove(settings: &mut NmSettings, group: &str, key: &str) {
+    if let Some(g) = settings.get_mut(group) {
+        g.remove(key);
+    }
+}
+
+fn connection_type_label(connection_type: &str) -> &str {
+    match connection_type {
+        "802-11-wireless" => "Wi-Fi",
+        "802-3-ethernet" => "Ethernet",
+        "wireguard" => "WireGuard",
+        "vpn" => "VPN",
+        "bridge" => "Bridge",
+        "bond" => "Bond",
+        "vlan" => "VLAN",
+        "gsm" | "cdma" => "Mobile broadband",
+        "bluetooth" => "Bluetooth",
+        other => other,
+    }
+}
+
+/// "address/prefix" (or just the address) for each dict of an aa{sv} like address-data.
+fn address_data(data: &dyn dbus::arg::RefArg) -> Vec<String> {
+    let Some(entries) = data.as_iter() else {
+        return Vec::new();
+    };
+    let mut addresses = Vec::new();
+    for entry in entries {
+        let (mut address, mut prefix) = (None, None);
+        let items: Vec<&dyn dbus::arg::RefArg> = entry.as_iter().map(|i| i.collect()).unwrap_or_default();
+        for pair in items.chunks(2) {
+            match (pair[0].as_str(), pair.get(1)) {
+                (Some("address"), Some(value)) => address = value.as_str().map(str::to_string),
+                (Some("prefix"), Some(value)) => prefix = value.as_u64(),
+                _ => {}
+            }
+        }
+        match (address, prefix) {
+            (Some(address), Some(prefix)) => addresses.push(format!("{}/{}", address, prefix)),
+            (Some(address), None) => addresses.push(address),
+            _ => {}
+        }
+    }
+    addresses
+}
+
+fn settings_addresses(settings: &NmSettings, group: &str) -> String {
+    settings
+        .get(group)
+        .and_then(|g| g.get("address-data"))
+        .map(|data| address_data(&data.0).join(", "))
+        .unwrap_or_default()
+}
+
+/// IPv6 addresses sent as a list of 16 byte arrays.
+fn ipv6_byte_list(list: &dyn dbus::arg::RefArg) -> Vec<String> {
+    list.as_iter()
+        .map(|items| {
+            items
+                .filter_map(|i| i.as_iter())
+                .filter_map(|bytes| <[u8; 16]>::try_from(bytes.filter_map(|b| b.as_u64()).map(|b| b as u8).collect::<Vec<u8>>()).ok())
+                .map(|bytes| std::net::Ipv6Addr::from(bytes).to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn parse_addresses(text: &str, ipv6: bool) -> Result<Vec<dbus::arg::PropMap>, String> {
+    let mut data = Vec::new();
+    for item in text.split(',').map(str::trim).filter(|i| !i.is_empty()) {
+        let (address, prefix) = item.split_once('/').ok_or_else(|| format!("{} has no /prefix", item))?;
+        let ip: std::net::IpAddr = address.parse().map_err(|_| format!("{} is not an IP address", address))?;
+        let prefix: u32 = prefix.parse().map_err(|_| format!("{} is not a prefix length", prefix))?;
+        if ip.is_ipv6() != ipv6 || prefix > if ipv6 { 128 } else { 32 } {
+            return Err(format!("{} doesn't fit here", item));
+        }
+        let mut entry = dbus::arg::PropMap::new();
+        entry.insert("address".to_string(), dbus::arg::Variant(Box::new(ip.to_string())));
+        entry.insert("prefix".to_string(), dbus::arg::Variant(Box::new(prefix)));
+        data.push(entry);
+    }
+    Ok(data)
+}
+
+/// ipv4.dns is a list of addresses in network byte order (read as native u32s), ipv6.dns a list of 16 byte arrays.
+fn settings_dns(settings: &NmSettings, group: &str) -> String {
+    let Some(dns) = settings.get(group).and_then(|g| g.get("dns")) else {
+        return String::new();
+    };
+    let servers: Vec<String> = if let Some(v4) = dbus::arg::cast::<Vec<u32>>(&*dns.0) {
+        v4.iter().map(|a| std::net::Ipv4Addr::from(a.to_ne_bytes()).to_string()).collect()
+    } else {
+        ipv6_byte_list(&dns.0)
+    };
+    servers.join(", ")
+}
+
+fn set_settings_dns(settings: &mut NmSettings, group: &str, text: &str, ipv6: bool) -> Result<(), String> {
+    let servers: Vec<&str> = text.split(',').map(str::trim).filter(|s| !s.is_empty()).collect();
+    if ipv6 {
+        let mut dns: Vec<Vec<u8>> = Vec::new();
+        for server in servers {
+            let ip: std::net::Ipv6Addr = server.parse().map_err(|_| format!("{} is not an IPv6 address", server))?;
+            dns.push(ip.octets().to_vec());
+        }
+        settings_set(settings, group, "dns", dns);
+    } else {
+        let mut dns: Vec<u32> = Vec::new();
+        for server in servers {
+            let ip: std::net::Ipv4Addr = server.parse().map_err(|_| format!("{} is not an IPv4 address", server))?;
+            dns.push(u32::from_ne_bytes(ip.octets()));
+        }
+        settings_set(settings, group, "dns", dns);
+    }
+    Ok(())
+}
+
+fn dropdown_select(dropdown: &gtk4::DropDown, options: &[&str], value: &str) {
+    dropdown.set_selected(options.iter().position(|o| *o == value).unwrap_or(0) as u32);
+}
+
+fn dropdown_value<'a>(dropdown: &gtk4::DropDown, options: &[&'a str]) -> &'a str {
+    options.get(dropdown.selected() as usize).copied().unwrap_or(options[0])
+}
+
+/// The selected string of a dropdown whose model changes at runtime.
+fn dropdown_string(dropdown: &gtk4::DropDown) -> Option<String> {
+    dropdown.selected_item().and_downcast::<gtk4::StringObject>().map(|o| o.string().to_string())
+}
+
+fn is_mac_address(text: &str) -> bool {
+    let parts: Vec<&str> = text.split(':').collect();
+    parts.len() == 6 && parts.iter().all(|p| p.len() == 2 && p.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+struct IpEditor {
+    group: &'static str,
+    methods: &'static [&'static str],
+    // the methods, plus a value NetworkManager knows and this list doesn't
+    method_list: gtk4::StringList,
+    method: gtk4::DropDown,
+    addresses: gtk4::Entry,
+    gateway: gtk4::Entry,
+    dns: gtk4::Entry,
+    ignore_auto_dns: gtk4::CheckButton,
+}
+
+impl IpEditor {
+    fn new(group: &'static str, methods: &'static [&'static str], grid: &gtk4::Grid, row: i32) -> Self {
+        let ipv6 = group == "ipv6";
+        let method_list = gtk4::StringList::new(methods);
+        let method = gtk4::DropDown::new(Some(method_list.clone()), None::<gtk4::Expression>);
+        let addresses = gtk4::Entry::builder()
+            .placeholder_text(if ipv6 { "fd00::20/64, ..." } else { "192.168.1.20/24, ..." })
+            .hexpand(true)
+            .build();
+        let gateway = gtk4::Entry::new();
+        let dns = gtk4::Entry::builder().placeholder_text(if ipv6 { "2606:4700:4700::1111, ..." } else { "1.1.1.1, 9.9.9.9" }).build();
+        let ignore_auto_dns = gtk4::CheckButton::with_label("only use these DNS servers");
+        let name = if ipv6 { "IPv6" } else { "IPv4" };
+        for (i, (label, widget)) in [
+            (format!("{} method", name), method.upcast_ref::<gtk4::Widget>()),
+            (format!("{} addresses", name), addresses.upcast_ref()),
+            (format!("{} gateway", name), gateway.upcast_ref()),
+            (format!("{} DNS", name), dns.upcast_ref()),
+            (String::new(), ignore_auto_dns.upcast_ref()),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let label = Label::new(Some(&label));
+            label.set_halign(gtk4::Align::Start);
+            grid.attach(&label, 0, row + i as i32, 1, 1);
+            grid.attach(widget, 1, row + i as i32, 1, 1);
+        }
+
+        // addresses only make sense for a manual configuration
+        let (addresses_clone, gateway_clone) = (addresses.clone(), gateway.clone());
+        method.connect_selected_notify(move |method| {
+            let manual = dropdown_string(method).as_deref() == Some("manual");
+            addresses_clone.set_sensitive(manual);
+            gateway_clone.set_sensitive(manual);
+        });
+
+        IpEditor { group, methods, method_list, method, addresses, gateway, dns, ignore_auto_dns }
+    }
+
+    fn load(&self, settings: &NmSettings) {
+        let method = settings_str(settings, self.group, "method").unwrap_or_else(|| "auto".to_string());
+        // an unknown method is listed as is, so saving doesn't rewrite it
+        let mut options = self.methods.to_vec();
+        if !options.contains(&method.as_str()) {
+            options.push(&method);
+        }
+        self.method_list.splice(0, self.method_list.n_items(), &options);
+        dropdown_select(&self.method, &options, &method);
+        self.addresses.set_text(&settings_addresses(settings, self.group));
+        self.gateway.set_text(&settings_str(settings, self.group, "gateway").unwrap_or_default());
+        self.dns.set_text(&settings_dns(settings, self.group));
+        self.ignore_auto_dns.set_active(settings_bool(settings, self.group, "ignore-auto-dns").unwrap_or(false));
+        let manual = method == "manual";
+        self.addresses.set_sensitive(manual);
+        self.gateway.set_sensitive(manual);
+    }
+
+    fn apply(&self, settings: &mut NmSettings) -> Result<(), String> {
+        let ipv6 = self.group == "ipv6";
+        let method = dropdown_string(&self.method).unwrap_or_else(|| self.methods[0].to_string());
+        let method = method.as_str();
+        settings_set(settings, self.group, "method", method.to_string());
+        // the legacy "addresses" would override address-data
+        settings_remove(settings, self.group, "addresses");
+        if method == "manual" {
+            let data = parse_addresses(&self.addresses.text(), ipv6)?;
+            if data.is_empty() {
+                return Err(format!("a manual {} configuration needs an address", self.group));
+            }
+            settings_set(settings, self.group, "address-data", data);
+            let gateway = self.gateway.text().trim().to_string();
+            if gateway.is_empty() {
+                settings_remove(settings, self.group, "gateway");
+            } else {
+                let ip: std::net::IpAddr = gateway.parse().map_err(|_| format!("{} is not a gateway address", gateway))?;
+                settings_set(settings, self.group, "gateway", ip.to_string());
+            }
+        } else {
+            settings_remove(settings, self.group, "address-data");
+            settings_remove(settings, self.group, "gateway");
+        }
+        set_settings_dns(settings, self.group, &self.dns.text(), ipv6)?;
+        settings_set(settings, self.group, "ignore-auto-dns", self.ignore_auto_dns.is_active());
+        Ok(())
+    }
+}
+
+struct ProfileEditor {
+    form: GtkBox,
+    title: Label,
+    name: gtk4::Entry,
+    autoconnect: Switch,
+    priority: gtk4::SpinButton,
+    metered: gtk4::DropDown,
+    mac_label: Label,
+    mac: gtk4::DropDown,
+    mac_custom: gtk4::Entry,
+    // "default", a MAC_OPTIONS keyword or a literal address, as loaded; saving only touches the
+    // MAC keys when this changed
+    mac_loaded: RefCell<String>,
+    ipv4: IpEditor,
+    ipv6: IpEditor,
+    save: Button,
+    revert: Button,
+    delete: Button,
+}
+
+impl ProfileEditor {
+    fn new() -> Self {
+        let form = GtkBox::new(Orientation::Vertical, 10);
+        form.set_hexpand(true);
+        let title = Label::new(None);
+        title.set_halign(gtk4::Align::Start);
+        title.set_css_classes(&["network_label"]);
+
+        let grid = gtk4::Grid::builder().row_spacing(8).column_spacing(20).build();
+        let name = gtk4::Entry::builder().hexpand(true).build();
+        let autoconnect = Switch::builder().halign(gtk4::Align::Start).build();
+        let priority = gtk4::SpinButton::with_range(-999.0, 999.0, 1.0);
+        priority.set_halign(gtk4::Align::Start);
+        let metered = gtk4::DropDown::from_strings(&METERED_OPTIONS);
+        let mac = gtk4::DropDown::from_strings(&MAC_OPTIONS);
+        let mac_custom = gtk4::Entry::builder().placeholder_text("12:34:56:78:9a:bc").hexpand(true).visible(false).build();
+        let mac_box = GtkBox::new(Orientation::Horizontal, 5);
+        mac_box.append(&mac);
+        mac_box.append(&mac_custom);
+        let mac_custom_clone = mac_custom.clone();
+        mac.connect_selected_notify(move |mac| {
+            mac_custom_clone.set_visible(dropdown_value(mac, &MAC_OPTIONS) == "custom");
+        });
+        let mac_label = Label::new(Some("MAC address"));
+        mac_label.set_halign(gtk4::Align::Start);
+        for (row, (label, widget)) in [
+            ("Name", name.upcast_ref::<gtk4::Widget>()),
+            ("Connect automatically", autoconnect.upcast_ref()),
+            ("Priority", priority.upcast_ref()),
+            ("Metered", metered.upcast_ref()),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let label = Label::new(Some(label));
+            label.set_halign(gtk4::Align::Start);
+            grid.attach(&label, 0, row as i32, 1, 1);
+            grid.attach(widget, 1, row as i32, 1, 1);
+        }
+        grid.attach(&mac_label, 0, 4, 1, 1);
+        grid.attach(&mac_box, 1, 4, 1, 1);
+        let ipv4 = IpEditor::new("ipv4", &IPV4_METHODS, &grid, 5);
+        let ipv6 = IpEditor::new("ipv6", &IPV6_METHODS, &grid, 10);
+
+        let buttons = GtkBox::new(Orientation::Horizontal, 5);
+        let delete = Button::with_label("Delete");
+        delete.set_hexpand(true);
+        delete.set_halign(gtk4::Align::Start);
+        let revert = Button::with_label("Revert");
+        let save = Button::with_label("Save");
+        buttons.append(&delete);
+        buttons.append(&revert);
+        buttons.append(&save);
+
+        form.append(&title);
+        form.append(&grid);
+        form.append(&buttons);
+        ProfileEditor {
+            form,
+            title,
+            name,
+            autoconnect,
+            priority,
+            metered,
+            mac_label,
+            mac,
+            mac_custom,
+            mac_loaded: RefCell::new(String::new()),
+            ipv4,
+            ipv6,
+            save,
+            revert,
+            delete,
+        }
+    }
+
+    /// The setting holding the MAC address, for the types that have one.
+    fn mac_group(settings: &NmSettings) -> Option<&'static str> {
+        match settings_str(settings, "connection", "type").as_deref() {
+            Some("802-11-wireless") => Some("802-11-wireless"),
+            Some("802-3-ethernet") => Some("802-3-ethernet"),
+            _ => None,
+        }
+    }
+
+    fn load(&self, settings: &NmSettings) {
+        let id = settings_str(settings, "connection", "id").unwrap_or_default();
+        let connection_type = settings_str(settings, "connection", "type").unwrap_or_default();
+        self.title.set_text(&format!("{}  ·  {}", id, connection_type_label(&connection_type)));
+        self.name.set_text(&id);
+        self.autoconnect.set_active(settings_bool(settings, "connection", "autoconnect").unwrap_or(true));
+        self.priority.set_value(settings_i32(settings, "connection", "autoconnect-priority").unwrap_or(0) as f64);
+        let metered = settings_i32(settings, "connection", "metered").unwrap_or(0);
+        self.metered.set_selected(metered.clamp(0, 2) as u32);
+
+        let mac_group = Self::mac_group(settings);
+        self.mac_label.set_visible(mac_group.is_some());
+        self.mac.set_visible(mac_group.is_some());
+        self.mac_custom.set_text("");
+        let mut loaded = "default".to_string();
+        if let Some(group) = mac_group {
+            // older profiles only carry the byte-array form
+            let cloned = settings.get(group).and_then(|g| g.get("cloned-mac-address")).and_then(|v| {
+                let bytes = dbus::arg::cast::<Vec<u8>>(&*v.0)?;
+                (bytes.len() == 6).then(|| bytes.iter().map(|b| format!("{:02x}", b)).collect::<Vec<_>>().join(":"))
+            });
+            loaded = settings_str(settings, group, "assigned-mac-address").or(cloned).unwrap_or(loaded);
+            if MAC_OPTIONS.contains(&loaded.as_str()) {
+                dropdown_select(&self.mac, &MAC_OPTIONS, &loaded);
+            } else {
+                dropdown_select(&self.mac, &MAC_OPTIONS, "custom");
+                self.mac_custom.set_text(&loaded);
+            }
+        }
+        let custom = mac_group.is_some() && dropdown_value(&self.mac, &MAC_OPTIONS) == "custom";
+        self.mac_custom.set_visible(custom);
+        *self.mac_loaded.borrow_mut() = loaded;
+        self.ipv4.load(settings);
+        self.ipv6.load(settings);
+    }
+
+    fn apply(&self, settings: &mut NmSettings) -> Result<(), String> {
+        let name = self.name.text().trim().to_string();
+        if name.is_empty() {
+            return Err("the profile needs a name".to_string());
+        }
+        settings_set(settings, "connection", "id", name);
+        settings_set(settings, "connection", "autoconnect", self.autoconnect.is_active());
+        settings_set(settings, "connection", "autoconnect-priority", self.priority.value_as_int());
+        settings_set(settings, "connection", "metered", self.metered.selected() as i32);
+        if let Some(group) = Self::mac_group(settings) {
+            let mac = match dropdown_value(&self.mac, &MAC_OPTIONS) {
+                "custom" => {
+                    let custom = self.mac_custom.text().trim().to_lowercase();
+                    if !is_mac_address(&custom) {
+                        return Err(format!("{} is not a MAC address", custom));
+                    }
+                    custom
+                }
+                keyword => keyword.to_string(),
+            };
+            if !mac.eq_ignore_ascii_case(&self.mac_loaded.borrow()) {
+                // the old byte-array form would win over assigned-mac-address
+                settings_remove(settings, group, "cloned-mac-address");
+                match mac.as_str() {
+                    "default" => settings_remove(settings, group, "assigned-mac-address"),
+                    mac => settings_set(settings, group, "assigned-mac-address", mac.to_string()),
+                }
+            }
+        }
+        // a VPN or loopback profile may not carry ip settings; leave those alone
+        if settings.contains_key("ipv4") {
+            self.ipv4.apply(settings)?;
+        }
+        if settings.contains_key("ipv6") {
+            self.ipv6.apply(settings)?;
+        }
+        Ok(())
+    }
+}
+
+/// Adds the profile's stored secrets to its settings, so Update keeps them.
+fn merge_connection_secrets(nm: &NmConn, path: &str, settings: &mut NmSettings) {
+    for group in ["802-11-wireless-security", "802-1x", "vpn", "wireguard"] {
+        if !settings.contains_key(group) {
+            continue;
+        }
+        let Ok((secrets,)) = nm_call::<(NmSettings,), _>(nm, path, NM_CONNECTION_IFACE, "GetSecrets", (group,)) else {
+            continue;
+        };
+        for (name, values) in secrets {
+            let target = settings.entry(name).or_default();
+            for (key, value) in values {
+                target.insert(key, value);
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+struct ConnectionsPage {
+    nm: NmConn,
+    notif_box: GtkBox,
+    list: gtk4::ListBox,
+    editor: Rc<ProfileEditor>,
+    selected: Rc<RefCell<Option<(dbus::Path<'static>, NmSettings)>>>,
+}
+
+impl ConnectionsPage {
+    fn show_profile(&self, path: dbus::Path<'static>) {
+        *self.selected.borrow_mut() = Some((path, HashMap::new()));
+        self.reload();
+    }
+
+    fn reload(&self) {
+        while let Some(child) = self.list.first_child() {
+            self.list.remove(&child);
+        }
+        let mut connections = nm_saved_connections(&self.nm);
+        connections.retain(|(_, settings)| settings_str(settings, "connection", "type").as_deref() != Some("loopback"));
+        connections.sort_by_key(|(_, settings)| settings_str(settings, "connection", "id").unwrap_or_default().to_lowercase());
+
+        let selected_path = self.selected.borrow().as_ref().map(|(path, _)| path.clone());
+        let mut still_there = false;
+        for (path, settings) in connections {
+            let id = settings_str(&settings, "connection", "id").unwrap_or_default();
+            let connection_type = settings_str(&settings, "connection", "type").unwrap_or_default();
+            let button = Button::with_label(&format!("{}  ·  {}", id, connection_type_label(&connection_type)));
+            button.set_css_classes(&["network_label"]);
+            if selected_path.as_ref() == Some(&path) {
+                button.add_css_class("connected");
+                *self.selected.borrow_mut() = Some((path.clone(), settings.clone_settings()));
+                still_there = true;
+            }
+            let page = self.clone();
+            button.connect_clicked(move |_| {
+                *page.selected.borrow_mut() = Some((path.clone(), settings.clone_settings()));
+                page.reload();
+            });
+            self.list.append(&button);
+        }
+
+        if !still_there {
+            *self.selected.borrow_mut() = None;
+        }
+        match &*self.selected.borrow() {
+            Some((_, settings)) => {
+                self.editor.load(settings);
+                self.editor.form.set_visible(true);
+            }
+            None => self.editor.form.set_visible(false),
+        }
+    }
+
+    fn save(&self) {
+        let Some((path, mut settings)) = self.selected.borrow().as_ref().map(|(p, s)| (p.clone(), s.clone_settings())) else {
+            return;
+        };
+        if let Err(e) = self.editor.apply(&mut settings) {
+            show_notification(&self.notif_box, &format!("not saved: {}", e));
+            return;
+        }
+        merge_connection_secrets(&self.nm, &path, &mut settings);
+        match nm_call::<(), _>(&self.nm, &path, NM_CONNECTION_IFACE, "Update", (settings,)) {
+            Ok(()) => show_notification(&self.notif_box, "connection profile saved"),
+            Err(e) => show_notification(&self.notif_box, &format!("not saved: {}", e.message().unwrap_or("NetworkManager refused"))),
+        }
+        self.reload();
+    }
+
+    fn delete(&self, parent: &impl IsA<gtk4::Widget>) {
+        let Some((path, settings)) = self.selected.borrow().as_ref().map(|(p, s)| (p.clone(), s.clone_settings())) else {
+            return;
+        };
+        let id = settings_str(&settings, "connection", "id").unwrap_or_default();
+        let dialog = MessageDialog::builder()
+            .text(format!("Delete {}?", id))
+            .secondary_text("Its password and settings are removed from NetworkManager.")
+            .modal(true)
+            .build();
+        if let Some(window) = parent.root().and_downcast::<gtk4::Window>() {
+            dialog.set_transient_for(Some(&window));
+        }
+        dialog.add_buttons(&[("Cancel", ResponseType::Cancel), ("Delete", ResponseType::Accept)]);
+        let page = self.clone();
+        dialog.connect_response(move |dialog, response| {
+            if response == ResponseType::Accept {
+                match nm_call::<(), _>(&page.nm, &path, NM_CONNECTION_IFACE, "Delete", ()) {
+                    Ok(()) => show_notification(&page.notif_box, &format!("{} deleted", id)),
+                    Err(e) => show_notification(&page.notif_box, &format!("{} not deleted: {}", id, e.message().unwrap_or("NetworkManager refused"))),
+                }
+                page.reload();
+            }
+            dialog.close();
+        });
+        dialog.show();
+    }
+}
+
+/// PropMap values are boxed trait objects without Clone.
+trait CloneSettings {
+    fn clone_settings(&self) -> NmSettings;
+}
+
+impl CloneSettings for NmSettings {
+    fn clone_settings(&self) -> NmSettings {
+        self.iter()
+            .map(|(group, values)| {
+                let values = values.iter().map(|(k, v)| (k.clone(), dbus::arg::Variant(v.0.box_clone()))).collect();
+                (group.clone(), values)
+            })
+            .collect()
+    }
+}
+
+fn build_connections_page(nm: &NmConn, notif_box: &GtkBox) -> (GtkBox, ConnectionsPage) {
+    let page_box = GtkBox::new(Orientation::Horizontal, 10);
+    page_box.set_hexpand(true);
+    page_box.set_vexpand(true);
+
+    let list = gtk4::ListBox::new();
+    list.set_selection_mode(gtk4::SelectionMode::None);
+    list.set_css_classes(&["network_list"]);
+    let list_scroller = ScrolledWindow::builder()
+        .hscrollbar_policy(gtk4::PolicyType::Never)
+        .child(&list)
+        .vexpand(true)
+        .width_request(450)
+        .build();
+    list_scroller.set_css_classes(&["display_win", "border_cell"]);
+
+    let editor = Rc::new(ProfileEditor::new());
+    let editor_scroller = ScrolledWindow::builder()
+        .hscrollbar_policy(gtk4::PolicyType::Never)
+        .child(&editor.form)
+        .hexpand(true)
+        .vexpand(true)
+        .build();
+    editor_scroller.set_css_classes(&["display_win"]);
+
+    page_box.append(&list_scroller);
+    page_box.append(&editor_scroller);
+
+    let page = ConnectionsPage {
+        nm: nm.clone(),
+        notif_box: notif_box.clone(),
+        list,
+        editor: editor.clone(),
+        selected: Rc::new(RefCell::new(None)),
+    };
+
+    let page_clone = page.clone();
+    editor.save.connect_clicked(move |_| page_clone.save());
+    let page_clone = page.clone();
+    editor.revert.connect_clicked(move |_| page_clone.reload());
+    let page_clone = page.clone();
+    editor.delete.connect_clicked(move |button| page_clone.delete(button));
+
+    (page_box, page)
+}
+
+// Wired ------------------------------------------------------------------------------------------------------------------------------------------- //
+// One row per Ethernet device, rebuilt whenever a device changes state or its link changes.
+
+const NM_WIRED_IFACE: &str = "org.freedesktop.NetworkManager.Device.Wired";
+const NM_IP4_CONFIG_IFACE: &str = "org.freedesktop.NetworkManager.IP4Config";
+const NM_IP6_CONFIG_IFACE: &str = "org.freedesktop.NetworkManager.IP6Config";
+const NM_DEVICE_TYPE_ETHERNET: u32 = 1;
+
+fn device_state_label(state: u32) -> &'static str {
+    match state {
+        10 => "unmanaged",
+        20 => "unavailable",
+        30 => "disconnected",
+        40..=90 => "connecting",
+        100 => "connected",
+        110 => "disconnecting",
+        120 => "failed",
+        _ => "unknown",
+    }
+}
+
+/// Addresses, gateway and DNS of a device's IP4Config or IP6Config object, one line.
+fn ip_config_summary(nm: &NmConn, config: &str, ipv6: bool) -> Option<String> {
+    if config == "/" {
+        return None;
+    }
+    let iface = if ipv6 { NM_IP6_CONFIG_IFACE } else { NM_IP4_CONFIG_IFACE };
+    let (props,) = nm_call::<(dbus::arg::PropMap,), _>(nm, config, NM_PROPERTIES_IFACE, "GetAll", (iface,)).ok()?;
+    let addresses = props.get("AddressData").map(|a| address_data(&a.0)).unwrap_or_default();
+    if addresses.is_empty() {
+        return None;
+    }
+    let dns = if ipv6 {
+        props.get("Nameservers").map(|n| ipv6_byte_list(&n.0)).unwrap_or_default()
+    } else {
+        props.get("NameserverData").map(|n| address_data(&n.0)).unwrap_or_default()
+    };
+    let mut line = format!("{}  {}", if ipv6 { "IPv6" } else { "IPv4" }, addresses.join(", "));
+    if let Some(gateway) = dbus::arg::prop_cast::<String>(&props, "Gateway").filter(|g| !g.is_empty()) {
+        line.push_str(&format!("   gateway {}", gateway));
+    }
+    if !dns.is_empty() {
+        line.push_str(&format!("   DNS {}", dns.join(", ")));
+    }
+    Some(line)
+}
+
+/// The profile a device is using, or else the first one it could use.
+fn device_profile(nm: &NmConn, device: &str) -> Option<dbus::Path<'static>> {
+    let active = nm_prop::<dbus::Path<'static>>(nm, device, NM_DEVICE_IFACE, "ActiveConnection").ok();
+    if let Some(profile) = active
+        .filter(|a| &**a != "/")
+        .and_then(|a| nm_prop::<dbus::Path<'static>>(nm, &a, NM_ACTIVE_IFACE, "Connection").ok())
+    {
+        return Some(profile);
+    }
+    nm_prop::<Vec<dbus::Path<'static>>>(nm, device, NM_DEVICE_IFACE, "AvailableConnections")
+        .ok()?
+        .into_iter()
+        .next()
+}
+
+#[derive(Clone)]
+struct WiredSection {
+    nm: NmConn,
+    notif_box: GtkBox,
+    section: GtkBox,
+    rows: GtkBox,
+    on_edit_profile: Rc<dyn Fn(dbus::Path<'static>)>,
+}
+
+impl WiredSection {
+    fn new(nm: &NmConn, notif_box: &GtkBox, on_edit_profile: impl Fn(dbus::Path<'static>) + 'static) -> Self {
+        let section = GtkBox::new(Orientation::Vertical, 5);
+        section.set_css_classes(&["network_row"]);
+        let title = Label::new(Some("WIRED"));
+        title.set_halign(gtk4::Align::Start);
+        title.set_css_classes(&["network_badge"]);
+        let rows = GtkBox::new(Orientation::Vertical, 5);
+        rows.set_css_classes(&["network_row"]);
+        section.append(&title);
+        section.append(&rows);
+        WiredSection {
+            nm: nm.clone(),
+            notif_box: notif_box.clone(),
+            section,
+            rows,
+            on_edit_profile: Rc::new(on_edit_profile),
+        }
+    }
+
+    fn reload(&self) {
+        while let Some(child) = self.rows.first_child() {
+            self.rows.remove(&child);
+        }
+        let devices = nm_devices_of_type(&self.nm, NM_DEVICE_TYPE_ETHERNET);
+        self.section.set_visible(!devices.is_empty());
+        for device in devices {
+            self.rows.append(&self.device_row(&device));
+        }
+    }
+
+    fn device_row(&self, device: &str) -> GtkBox {
+        let nm = &self.nm;
+        let interface = nm_prop::<String>(nm, device, NM_DEVICE_IFACE, "Interface").unwrap_or_default();
+        let state = nm_prop::<u32>(nm, device, NM_DEVICE_IFACE, "State").unwrap_or(0);
+        let carrier = nm_prop::<bool>(nm, device, NM_WIRED_IFACE, "Carrier").unwrap_or(false);
+        let speed = nm_prop::<u32>(nm, device, NM_WIRED_IFACE, "Speed").unwrap_or(0);
+
+        let row = GtkBox::new(Orientation::Vertical, 0);
+        row.set_css_classes(&["network_row"]);
+        let top = GtkBox::new(Orientation::Horizontal, 10);
+        top.set_css_classes(&["network_row"]);
+        let mut heading = format!("{}  ·  {}", interface, if carrier { device_state_label(state) } else { "cable unplugged" });
+        if carrier && speed > 0 {
+            heading.push_str(&format!("  ·  {} Mb/s", speed));
+        }
+        let name = Label::new(Some(&heading));
+        name.set_hexpand(true);
+        name.set_halign(gtk4::Align::Start);
+        name.set_css_classes(&["network_label"]);
+        if state == 100 {
+            name.add_css_class("connected");
+        }
+        let profile = Button::with_label("Profile");
+        profile.set_valign(gtk4::Align::Center);
+        let switch = Switch::builder()
+            .active((40..=100).contains(&state))
+            .sensitive(carrier || state >= 40)
+            .valign(gtk4::Align::Center)
+            .build();
+        top.append(&name);
+        top.append(&profile);
+        top.append(&switch);
+        row.append(&top);
+
+        let lines: Vec<String> = [("Ip4Config", false), ("Ip6Config", true)]
+            .into_iter()
+            .filter_map(|(prop, ipv6)| {
+                let config = nm_prop::<dbus::Path<'static>>(nm, device, NM_DEVICE_IFACE, prop).ok()?;
+                ip_config_summary(nm, &config, ipv6)
+            })
+            .collect();
+        if !lines.is_empty() {
+            let details = Label::new(Some(&lines.join("\n")));
+            details.set_halign(gtk4::Align::Start);
+            details.set_selectable(true);
+            details.set_css_classes(&["network_details"]);
+            row.append(&details);
+        }
+
+        let section = self.clone();
+        let device_clone = device.to_string();
+        switch.connect_state_set(move |_, on| {
+            let result = if on {
+                let device = dbus::Path::from(device_clone.clone());
+                nm_call::<(dbus::Path<'static>,), _>(&section.nm, NM_PATH, NM_IFACE, "ActivateConnection", (dbus::Path::from("/"), device, dbus::Path::from("/")))
+                    .map(|_| ())
+            } else {
+                nm_call::<(), _>(&section.nm, &device_clone, NM_DEVICE_IFACE, "Disconnect", ())
+            };
+            if let Err(e) = result {
+                show_notification(&section.notif_box, &format!("{}: {}", interface, e.message().unwrap_or("NetworkManager refused")));
+            }
+            glib::Propagation::Proceed
+        });
+
+        let section = self.clone();
+        let device = device.to_string();
+        profile.connect_clicked(move |_| match device_profile(&section.nm, &device) {
+            Some(path) => (section.on_edit_profile)(path),
+            None => show_notification(&section.notif_box, "this device has no connection profile"),
+        });
+        row
+    }
+}
+
+fn watch_wired_signals(section: &WiredSection) {
+    use dbus::message::MatchRule;
+    let nm = &section.nm;
+
+    // state changes come as a signal on the device; carrier, speed and new IP configs as properties
+    let section_clone = section.clone();
+    let state = MatchRule::new_signal(NM_DEVICE_IFACE, "StateChanged")
+        .with_sender(NM_BUS)
+        .with_namespaced_path(format!("{}/Devices", NM_PATH));
+    let _ = nm.add_match(state, move |(_, _, _): (u32, u32, u32), _, msg| {
+        let path = msg.path().map(|p| p.to_string()).unwrap_or_default();
+        if nm_prop::<u32>(&section_clone.nm, &path, NM_DEVICE_IFACE, "DeviceType").ok() == Some(NM_DEVICE_TYPE_ETHERNET) {
+            section_clone.reload();
+        }
+        true
+    });
+
+    let section_clone = section.clone();
+    let changed = MatchRule::new_signal(NM_PROPERTIES_IFACE, "PropertiesChanged")
+        .with_sender(NM_BUS)
+        .with_namespaced_path(format!("{}/Devices", NM_PATH));
+    let _ = nm.add_match(changed, move |(iface, props, _): (String, dbus::arg::PropMap, Vec<String>), _, _| {
+        let relevant = match iface.as_str() {
+            NM_WIRED_IFACE => props.contains_key("Carrier") || props.contains_key("Speed"),
+            _ => false,
+        };
+        if relevant {
+            section_clone.reload();
+        }
+        true
+    });
+
+    for member in ["DeviceAdded", "DeviceRemoved"] {
+        let section_clone = section.clone();
+        let rule = MatchRule::new_signal(NM_IFACE, member).with_sender(NM_BUS).with_path(NM_PATH);
+        let _ = nm.add_match(rule, move |(_,): (dbus::Path<'static>,), _, _| {
+            section_clone.reload();
+            true
+        });
+    }
+}
+
+// VPN --------------------------------------------------------------------------------------------------------------------------------------------- //
+// NetworkManager VPN plugin profiles and WireGuard profiles. Imports go through nmcli, which
+// hands the file to the matching import plugin. NetworkManager doesn't publish WireGuard
+// handshakes, so those come from `wg show`. Reading a WireGuard interface takes CAP_NET_ADMIN,
+// which a desktop session doesn't have; the row then says the handshake is unknown instead of
+// pretending there was none.
+
+fn is_vpn_profile(settings: &NmSettings) -> bool {
+    matches!(settings_str(settings, "connection", "type").as_deref(), Some("vpn" | "wireguard"))
+}
+
+/// profile path -> (active connection path, state) for everything NetworkManager has up.
+fn nm_active_profiles(nm: &NmConn) -> HashMap<String, (String, u32)> {
+    let active = nm_prop::<Vec<dbus::Path<'static>>>(nm, NM_PATH, NM_IFACE, "ActiveConnections").unwrap_or_default();
+    active
+        .into_iter()
+        .filter_map(|path| {
+            let profile = nm_prop::<dbus::Path<'static>>(nm, &path, NM_ACTIVE_IFACE, "Connection").ok()?;
+            let state = nm_prop::<u32>(nm, &path, NM_ACTIVE_IFACE, "State").unwrap_or(0);
+            Some((profile.to_string(), (path.to_string(), state)))
+        })
+        .collect()
+}
+
+fn active_state_label(state: u32) -> &'static str {
+    match state {
+        1 => "connecting",
+        2 => "connected",
+        3 => "disconnecting",
+        _ => "disconnected",
+    }
+}
+
+/// Endpoints of the peers in a WireGuard profile.
+fn wireguard_endpoints(settings: &NmSettings) -> Vec<String> {
+    let Some(peers) = settings.get("wireguard").and_then(|g| g.get("peers")).and_then(|p| p.0.as_iter()) else {
+        return Vec::new();
+    };
+    let mut endpoints = Vec::new();
+    for peer in peers {
+        let items: Vec<&dyn dbus::arg::RefArg> = peer.as_iter().map(|i| i.collect()).unwrap_or_default();
+        for pair in items.chunks(2) {
+            if let (Some("endpoint"), Some(value)) = (pair[0].as_str(), pair.get(1).and_then(|v| v.as_str())) {
+                endpoints.push(value.to_string());
+            }
+        }
+    }
+    endpoints
+}
+
+enum Handshake {
+    SecondsAgo(u64),
+    Never,
+    // `wg` lacks CAP_NET_ADMIN
+    NotPermitted,
+    NoWg,
+    Failed(String),
+}
+
+impl Handshake {
+    fn label(&self) -> String {
+        match self {
+            Handshake::SecondsAgo(seconds) if *seconds < 120 => format!("last handshake {} s ago", seconds),
+            Handshake::SecondsAgo(seconds) if *seconds < 7200 => format!("last handshake {} min ago", seconds / 60),
+            Handshake::SecondsAgo(seconds) => format!("last handshake {} h ago", seconds / 3600),
+            Handshake::Never => "no handshake yet".to_string(),
+            Handshake::NotPermitted => "handshake unknown".to_string(),
+            Handshake::NoWg => "install wireguard-tools to see handshakes".to_string(),
+            Handshake::Failed(e) => format!("handshake unknown: {}", e),
+        }
+    }
+}
+
+/// The most recent handshake of any peer on a WireGuard interface, from `wg show`.
+async fn wireguard_last_handshake(interface: String) -> Handshake {
+    let args = ["wg", "show", interface.as_str(), "latest-handshakes"];
+    let argv: Vec<&std::ffi::OsStr> = args.iter().map(std::ffi::OsStr::new).collect();
+    let flags = gtk4::gio::SubprocessFlags::STDOUT_PIPE | gtk4::gio::SubprocessFlags::STDERR_PIPE;
+    let Ok(process) = gtk4::gio::Subprocess::newv(&argv, flags) else {
+        return Handshake::NoWg;
+    };
+    let (stdout, stderr) = match process.communicate_utf8_future(None).await {
+        Ok((stdout, stderr)) => (stdout.unwrap_or_default(), stderr.unwrap_or_default()),
+        Err(e) => return Handshake::Failed(e.to_string()),
+    };
+    if !process.is_successful() {
+        return if stderr.contains("Operation not permitted") {
+            Handshake::NotPermitted
+        } else {
+            Handshake::Failed(stderr.trim().to_string())
+        };
+    }
+    let latest = stdout
+        .lines()
+        .filter_map(|line| line.split_whitespace().nth(1)?.parse::<u64>().ok())
+        .filter(|t| *t > 0)
+        .max();
+    let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    match latest {
+        Some(latest) => Handshake::SecondsAgo(now.saturating_sub(latest)),
+        None => Handshake::Never,
+    }
+}
+
+async fn import_vpn_file(path: &Path) -> Result<String, String> {
+    let ext = path.extension().and_then(|e| e.to_str()).map(|e| e.to_lowercase());
+    let vpn_type = match ext.as_deref() {
+        Some("conf") => "wireguard",
+        Some("ovpn") => "openvpn",
+        _ => return Err("only WireGuard .conf and OpenVPN .ovpn files can be imported".to_string()),
+    };
+    let args = ["nmcli", "connection", "import", "type", vpn_type, "file"];
+    let mut argv: Vec<&std::ffi::OsStr> = args.iter().map(std::ffi::OsStr::new).collect();
+    argv.push(path.as_os_str());
+    let flags = gtk4::gio::SubprocessFlags::STDOUT_SILENCE | gtk4::gio::SubprocessFlags::STDERR_PIPE;
+    let process = gtk4::gio::Subprocess::newv(&argv, flags).map_err(|e| format!("nmcli: {}", e))?;
+    let (_, stderr) = process.communicate_utf8_future(None).await.map_err(|e| format!("nmcli: {}", e))?;
+    if process.is_successful() {
+        Ok(path.file_stem().unwrap_or_default().to_string_lossy().to_string())
+    } else {
+        Err(stderr.unwrap_or_default().trim().to_string())
+    }
+}
+
+#[derive(Clone)]
+struct VpnSection {
+    nm: NmConn,
+    notif_box: GtkBox,
+    section: GtkBox,
+    rows: GtkBox,
+    // saved VPN profiles, fetched again only when profiles are added or removed
+    profiles: Rc<RefCell<Vec<(dbus::Path<'static>, NmSettings)>>>,
+    // interface and handshake line of each connected WireGuard row
+    handshakes: Rc<RefCell<Vec<(String, Label)>>>,
+}
+
+impl VpnSection {
+    fn new(nm: &NmConn, notif_box: &GtkBox, window: &ApplicationWindow) -> Self {
+        let section = GtkBox::new(Orientation::Vertical, 5);
+        section.set_css_classes(&["network_row"]);
+        let header = GtkBox::new(Orientation::Horizontal, 5);
+        header.set_css_classes(&["network_row"]);
+        let title = Label::new(Some("VPN"));
+        title.set_halign(gtk4::Align::Start);
+        title.set_hexpand(true);
+        title.set_css_classes(&["network_badge"]);
+        let import = Button::with_label("Import…");
+        header.append(&title);
+        header.append(&import);
+        let rows = GtkBox::new(Orientation::Vertical, 5);
+        rows.set_css_classes(&["network_row"]);
+        section.append(&header);
+        section.append(&rows);
+
+        let vpn = VpnSection {
+            nm: nm.clone(),
+            notif_box: notif_box.clone(),
+            section,
+            rows,
+            profiles: Rc::new(RefCell::new(Vec::new())),
+            handshakes: Rc::new(RefCell::new(Vec::new())),
+        };
+        let (vpn_clone, window) = (vpn.clone(), window.clone());
+        import.connect_clicked(move |_| {
+            let dialog = FileChooserDialog::new(
+                Some("Import a VPN configuration"),
+                Some(&window),
+                FileChooserAction::Open,
+                &[("_Cancel", ResponseType::Cancel), ("_Import", ResponseType::Accept)],
+            );
+            dialog.set_css_classes(&["wall-dialog"]);
+            dialog.set_size_request(400, 800);
+            add_class_recursive(dialog.upcast_ref(), "wall-dialog");
+            let filter = gtk4::FileFilter::new();
+            filter.set_name(Some("WireGuard and OpenVPN"));
+            filter.add_pattern("*.conf");
+            filter.add_pattern("*.ovpn");
+            dialog.add_filter(&filter);
+
+            let vpn = vpn_clone.clone();
+            dialog.connect_response(move |dialog, response| {
+                let source = dialog.file().and_then(|f| f.path());
+                dialog.close();
+                let (ResponseType::Accept, Some(source)) = (response, source) else {
+                    return;
+                };
+                let vpn = vpn.clone();
+                glib::MainContext::default().spawn_local(async move {
+                    match import_vpn_file(&source).await {
+                        Ok(name) => show_notification(&vpn.notif_box, &format!("{} imported", name)),
+                        Err(e) => show_notification(&vpn.notif_box, &format!("not imported: {}", e)),
+                    }
+                    vpn.reload();
+                });
+            });
+            dialog.show();
+        });
+        vpn
+    }
+
+    fn reload(&self) {
+        let mut profiles: Vec<_> = nm_saved_connections(&self.nm).into_iter().filter(|(_, s)| is_vpn_profile(s)).collect();
+        profiles.sort_by_key(|(_, settings)| settings_str(settings, "connection", "id").unwrap_or_default().to_lowercase());
+        *self.profiles.borrow_mut() = profiles;
+        self.render();
+    }
+
+    /// Rebuilds the rows from the cached profiles and NetworkManager's active connections.
+    fn render(&self) {
+        while let Some(child) = self.rows.first_child() {
+            self.rows.remove(&child);
+        }
+        self.handshakes.borrow_mut().clear();
+        let active = nm_active_profiles(&self.nm);
+        let profiles: Vec<_> = self.profiles.borrow().iter().map(|(p, s)| (p.clone(), s.clone_settings())).collect();
+        if profiles.is_empty() {
+            let empty = Label::new(Some("no VPN connections, import a WireGuard or OpenVPN file"));
+            empty.set_halign(gtk4::Align::Start);
+            empty.set_opacity(0.6);
+            self.rows.append(&empty);
+        }
+        for (path, settings) in profiles {
+            let state = active.get(&path.to_string()).cloned();
+            self.rows.append(&self.profile_row(path, &settings, state));
+        }
+        self.refresh_handshakes();
+    }
+
+    fn refresh_handshakes(&self) {
+        for (interface, label) in self.handshakes.borrow().iter() {
+            let (interface, label) = (interface.clone(), label.clone());
+            glib::MainContext::default().spawn_local(async move {
+                let handshake = wireguard_last_handshake(interface).await;
+                label.set_text(&handshake.label());
+            });
+        }
+    }
+
+    fn profile_row(&self, path: dbus::Path<'static>, settings: &NmSettings, active: Option<(String, u32)>) -> GtkBox {
+        let id = settings_str(settings, "connection", "id").unwrap_or_default();
+        let wireguard = settings_str(settings, "connection", "type").as_deref() == Some("wireguard");
+        let kind = if wireguard {
+            "WireGuard".to_string()
+        } else {
+            let service = settings_str(settings, "vpn", "service-type").unwrap_or_default();
+            service.rsplit('.').next().unwrap_or("VPN").to_string()
+        };
+        let state = active.as_ref().map(|(_, state)| *state).unwrap_or(0);
+
+        let row = GtkBox::new(Orientation::Vertical, 0);
+        row.set_css_classes(&["network_row"]);
+        let top = GtkBox::new(Orientation::Horizontal, 10);
+        top.set_css_classes(&["network_row"]);
+        let name = Label::new(Some(&format!("{}  ·  {}  ·  {}", id, kind, active_state_label(state))));
+        name.set_hexpand(true);
+        name.set_halign(gtk4::Align::Start);
+        name.set_css_classes(&["network_label"]);
+        if state == 2 {
+            name.add_css_class("connected");
+        }
+        let switch = Switch::builder().active(matches!(state, 1 | 2)).valign(gtk4::Align::Center).build();
+        top.append(&name);
+        top.append(&switch);
+        row.append(&top);
+
+        let mut lines = Vec::new();
+        if wireguard {
+            let endpoints = wireguard_endpoints(settings);
+            if !endpoints.is_empty() {
+                lines.push(format!("endpoint {}", endpoints.join(", ")));
+            }
+        } else if let Some(gateway) = settings.get("vpn").and_then(|g| g.get("data")).and_then(|d| d.0.as_iter()) {
+            // vpn.data is a string dict; the gateway key is "remote" for OpenVPN, "gateway" for most others
+            let items: Vec<&dyn dbus::arg::RefArg> = gateway.collect();
+            for pair in items.chunks(2) {
+                if let (Some("remote" | "gateway"), Some(value)) = (pair[0].as_str(), pair.get(1).and_then(|v| v.as_str())) {
+                    lines.push(format!("server {}", value));
+                }
+            }
+        }
+        let config = active
+            .as_ref()
+            .filter(|(_, state)| *state == 2)
+            .and_then(|(active_path, _)| nm_prop::<dbus::Path<'static>>(&self.nm, active_path, NM_ACTIVE_IFACE, "Ip4Config").ok());
+        if let Some(config) = config {
+            lines.extend(ip_config_summary(&self.nm, &config, false));
+        }
+        if !lines.is_empty() {
+            let details = Label::new(Some(&lines.join("\n")));
+            details.set_halign(gtk4::Align::Start);
+            details.set_selectable(true);
+            details.set_css_classes(&["network_details"]);
+            row.append(&details);
+        }
+        // filled in by refresh_handshakes, which the timer repeats without rebuilding the row
+        if wireguard && state == 2 {
+            let handshake = Label::new(Some("checking handshake…"));
+            handshake.set_halign(gtk4::Align::Start);
+            handshake.set_wrap(true);
+            handshake.set_selectable(true);
+            handshake.set_css_classes(&["network_details"]);
+            row.append(&handshake);
+            let interface = settings_str(settings, "connection", "interface-name").unwrap_or_default();
+            self.handshakes.borrow_mut().push((interface, handshake));
+        }
+
+        let vpn = self.clone();
+        switch.connect_state_set(move |_, on| {
+            let result = match (&active, on) {
+                (None, true) => nm_call::<(dbus::Path<'static>,), _>(
+                    &vpn.nm,
+                    NM_PATH,
+                    NM_IFACE,
+                    "ActivateConnection",
+                    (path.clone(), dbus::Path::from("/"), dbus::Path::from("/")),
+                )
+                .map(|_| ()),
+                (Some((active_path, _)), false) => {
+                    nm_call::<(), _>(&vpn.nm, NM_PATH, NM_IFACE, "DeactivateConnection", (dbus::Path::from(active_path.clone()),))
+                }
+                _ => Ok(()),
+            };
+            if let Err(e) = result {
+                show_notification(&vpn.notif_box, &format!("{}: {}", id, e.message().unwrap_or("NetworkManager refused")));
+            }
+            glib::Propagation::Proceed
+        });
+        row
+    }
+}
+
+fn watch_vpn_signals(vpn: &VpnSection) {
+    use dbus::message::MatchRule;
+    let nm = &vpn.nm;
+
+    let vpn_clone = vpn.clone();
+    let state = MatchRule::new_signal(NM_ACTIVE_IFACE, "StateChanged")
+        .with_sender(NM_BUS)
+        .with_namespaced_path(format!("{}/ActiveConnection", NM_PATH));
+    let _ = nm.add_match(state, move |(_, _): (u32, u32), _, _| {
+        vpn_clone.render();
+        true
+    });
+
+    for member in ["NewConnection", "ConnectionRemoved"] {
+        let vpn_clone = vpn.clone();
+        let rule = MatchRule::new_signal(NM_SETTINGS_IFACE, member)
+            .with_sender(NM_BUS)
+            .with_path(format!("{}/Settings", NM_PATH));
+        let _ = nm.add_match(rule, move |(_,): (dbus::Path<'static>,), _, _| {
+            vpn_clone.reload();
+            true
+        });
+    }
+
+    // handshake ages go stale; only worth refreshing while the page is shown
+    let vpn_clone = vpn.clone();
+    glib::timeout_add_seconds_local(10, move || {
+        if vpn_clone.section.is_mapped() {
+            vpn_clone.refresh_handshakes();
+        }
+        glib::ControlFlow::Continue
+    });
+}
+
+// Hotspot ----------------------------------------------------------------------------------------------------------------------------------------- //
+// An AP-mode profile on the Wi-Fi device with IPv4 "shared", so NetworkManager NATs whatever
+// else is connected (usually the cable). Clients are read from the dnsmasq leases NM keeps.
+
+const HOTSPOT_ID: &str = "Calibrate Hotspot";
+const HOTSPOT_BANDS: [&str; 3] = ["", "bg", "a"];
+const HOTSPOT_BAND_LABELS: [&str; 3] = ["Automatic", "2.4 GHz", "5 GHz"];
+
+struct HotspotConfig {
+    ssid: String,
+    password: String,
+    // "" lets NetworkManager choose, otherwise "bg" or "a"
+    band: String,
+}
+
+impl HotspotConfig {
+    fn default_config() -> Self {
+        let password: String = glib::uuid_string_random().chars().filter(|c| *c != '-').take(12).collect();
+        HotspotConfig { ssid: format!("{}-hotspot", glib::host_name()), password, band: String::new() }
+    }
+
+    fn settings(&self, uuid: Option<String>) -> NmSettings {
+        let mut settings: NmSettings = HashMap::new();
+        settings_set(&mut settings, "connection", "id", HOTSPOT_ID.to_string());
+        settings_set(&mut settings, "connection", "type", "802-11-wireless".to_string());
+        settings_set(&mut settings, "connection", "autoconnect", false);
+        if let Some(uuid) = uuid {
+            settings_set(&mut settings, "connection", "uuid", uuid);
+        }
+        settings_set(&mut settings, "802-11-wireless", "ssid", self.ssid.clone().into_bytes());
+        settings_set(&mut settings, "802-11-wireless", "mode", "ap".to_string());
+        if !self.band.is_empty() {
+            settings_set(&mut settings, "802-11-wireless", "band", self.band.clone());
+        }
+        let wsec = "802-11-wireless-security";
+        settings_set(&mut settings, wsec, "key-mgmt", "wpa-psk".to_string());
+        settings_set(&mut settings, wsec, "psk", self.password.clone());
+        // WPA2 only, phones refuse TKIP hotspots
+        settings_set(&mut settings, wsec, "proto", vec!["rsn".to_string()]);
+        settings_set(&mut settings, wsec, "pairwise", vec!["ccmp".to_string()]);
+        settings_set(&mut settings, wsec, "group", vec!["ccmp".to_string()]);
+        settings_set(&mut settings, "ipv4", "method", "shared".to_string());
+        settings_set(&mut settings, "ipv6", "method", "ignore".to_string());
+        settings
+    }
+}
+
+fn hotspot_profile(nm: &NmConn) -> Option<(dbus::Path<'static>, NmSettings)> {
+    nm_saved_connections(nm)
+        .into_iter()
+        .find(|(_, settings)| settings_str(settings, "connection", "id").as_deref() == Some(HOTSPOT_ID))
+}
+
+fn read_hotspot_config(nm: &NmConn) -> HotspotConfig {
+    let Some((path, mut settings)) = hotspot_profile(nm) else {
+        return HotspotConfig::default_config();
+    };
+    merge_connection_secrets(nm, &path, &mut settings);
+    HotspotConfig {
+        ssid: settings_ssid(&settings).unwrap_or_default(),
+        password: settings_str(&settings, "802-11-wireless-security", "psk").unwrap_or_default(),
+        band: settings_str(&settings, "802-11-wireless", "band").unwrap_or_default(),
+    }
+}
+
+/// "hostname  ip  mac" for each DHCP lease handed out on the interface.
+fn hotspot_clients(interface: &str) -> Vec<String> {
+    let leases = format!("/var/lib/NetworkManager/dnsmasq-{}.leases", interface);
+    let Ok(content) = fs::read_to_string(leases) else {
+        return Vec::new();
+    };
+    content
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let (mac, ip) = (fields.get(1)?, fields.get(2)?);
+            let host = fields.get(3).filter(|h| **h != "*").unwrap_or(&"unknown");
+            Some(format!("{}  {}  {}", host, ip, mac))
+        })
+        .collect()
+}
+
+#[derive(Clone)]
+struct Hotspot {
+    nm: NmConn,
+    notif_box: GtkBox,
+    wifi_page: WifiPage,
+    section: GtkBox,
+    switch: Switch,
+    status: Label,
+    clients: Label,
+    // active connection path while the hotspot is up
+    active: Rc<RefCell<Option<String>>>,
+    // set while reload() moves the switch, so that isn't taken as the user's toggle
+    updating: Rc<std::cell::Cell<bool>>,
+    // set while the radio was just turned on and the device is still unavailable; the device's
+    // StateChanged activates the hotspot once it can
+    pending_start: Rc<std::cell::Cell<bool>>,
+    // gives up on a pending start after a while
+    start_timeout: Rc<RefCell<Option<glib::SourceId>>>,
+}
+
+impl Hotspot {
+    fn new(wifi_page: &WifiPage, window: &ApplicationWindow) -> Self {
+        let section = GtkBox::new(Orientation::Vertical, 5);
+        section.set_css_classes(&["network_row"]);
+        let header = GtkBox::new(Orientation::Horizontal, 10);
+        header.set_css_classes(&["network_row"]);
+        let title = Label::new(Some("HOTSPOT"));
+        title.set_css_classes(&["network_badge"]);
+        let status = Label::new(None);
+        status.set_hexpand(true);
+        status.set_halign(gtk4::Align::Start);
+        let settings_button = Button::with_label("Settings…");
+        settings_button.set_valign(gtk4::Align::Center);
+        let switch = Switch::builder().valign(gtk4::Align::Center).build();
+        header.append(&title);
+        header.append(&status);
+        header.append(&settings_button);
+        header.append(&switch);
+        let clients = Label::new(None);
+        clients.set_halign(gtk4::Align::Start);
+        clients.set_selectable(true);
+        clients.set_css_classes(&["network_details"]);
+        section.append(&header);
+        section.append(&clients);
+
+        let hotspot = Hotspot {
+            nm: wifi_page.nm.clone(),
+            notif_box: wifi_page.notif_box.clone(),
+            wifi_page: wifi_page.clone(),
+            section,
+            switch: switch.clone(),
+            status,
+            clients,
+            active: Rc::new(RefCell::new(None)),
+            updating: Rc::new(std::cell::Cell::new(false)),
+            pending_start: Rc::new(std::cell::Cell::new(false)),
+            start_timeout: Rc::new(RefCell::new(None)),
+        };
+
+        let hotspot_clone = hotspot.clone();
+        switch.connect_state_set(move |_, on| {
+            if !hotspot_clone.updating.get() {
+                if on {
+                    hotspot_clone.start();
+                } else {
+                    hotspot_clone.stop();
+                }
+            }
+            glib::Propagation::Proceed
+        });
+        let (hotspot_clone, window) = (hotspot.clone(), window.clone());
+        settings_button.connect_clicked(move |_| open_hotspot_settings(&hotspot_clone, &window));
+        hotspot
+    }
+
+    fn device(&self) -> Option<String> {
+        nm_devices_of_type(&self.nm, NM_DEVICE_TYPE_WIFI).into_iter().next()
+    }
+
+    fn start(&self) {
+        let Some(device) = self.device() else {
+            show_notification(&self.notif_box, "no Wi-Fi adapter for a hotspot");
+            self.reload();
+            return;
+        };
+        // the radio switch being off would fail the activation
+        if !nm_prop::<bool>(&self.nm, NM_PATH, NM_IFACE, "WirelessEnabled").unwrap_or(false) {
+            if let Err(e) = set_wifi_enabled(&self.nm, true) {
+                show_notification(&self.notif_box, &format!("hotspot not started: {}", e.message().unwrap_or("could not turn on Wi-Fi")));
+                self.reload();
+                return;
+            }
+        }
+        // a device that is still unavailable (20) would refuse the activation
+        if nm_prop::<u32>(&self.nm, &device, NM_DEVICE_IFACE, "State").unwrap_or(0) <= 20 {
+            self.pending_start.set(true);
+            self.status.set_text("waiting for the Wi-Fi adapter…");
+            let hotspot = self.clone();
+            let timeout = glib::timeout_add_seconds_local_once(15, move || {
+                hotspot.start_timeout.borrow_mut().take();
+                if hotspot.pending_start.replace(false) {
+                    show_notification(&hotspot.notif_box, "hotspot not started: the Wi-Fi adapter didn't become ready");
+                    hotspot.reload();
+                }
+            });
+            if let Some(previous) = self.start_timeout.borrow_mut().replace(timeout) {
+                previous.remove();
+            }
+            return;
+        }
+        self.activate(device);
+    }
+
+    /// Called from the device's StateChanged while a start is pending.
+    fn device_ready(&self) {
+        if self.pending_start.replace(false) {
+            self.cancel_start_timeout();
+            match self.device() {
+                Some(device) => self.activate(device),
+                None => self.reload(),
+            }
+        }
+    }
+
+    fn activate(&self, device: String) {
+        let result = match hotspot_profile(&self.nm) {
+            Some((path, _)) => nm_call::<(dbus::Path<'static>,), _>(
+                &self.nm,
+                NM_PATH,
+                NM_IFACE,
+                "ActivateConnection",
+                (path, dbus::Path::from(device), dbus::Path::from("/")),
+            )
+            .map(|_| ()),
+            None => nm_call::<(dbus::Path<'static>, dbus::Path<'static>), _>(
+                &self.nm,
+                NM_PATH,
+                NM_IFACE,
+                "AddAndActivateConnection",
+                (HotspotConfig::default_config().settings(None), dbus::Path::from(device), dbus::Path::from("/")),
+            )
+            .map(|_| ()),
+        };
+        if let Err(e) = result {
+            show_notification(&self.notif_box, &format!("hotspot not started: {}", e.message().unwrap_or("NetworkManager refused")));
+            self.reload();
+        }
+    }
+
+    fn cancel_start_timeout(&self) {
+        if let Some(timeout) = self.start_timeout.borrow_mut().take() {
+            timeout.remove();
+        }
+    }
+
+    fn stop(&self) {
+        self.pending_start.set(false);
+        self.cancel_start_timeout();
+        let Some(active) = self.active.borrow().clone() else {
+            return;
+        };
+        if let Err(e) = nm_call::<(), _>(&self.nm, NM_PATH, NM_IFACE, "DeactivateConnection", (dbus::Path::from(active),)) {
+            show_notification(&self.notif_box, &format!("hotspot not stopped: {}", e.message().unwrap_or("NetworkManager refused")));
+        }
+    }
+
+    fn reload(&self) {
+        let profile = hotspot_profile(&self.nm);
+        let active = profile
+            .as_ref()
+            .and_then(|(path, _)| nm_active_profiles(&self.nm).remove(&path.to_string()))
+            .filter(|(_, state)| matches!(state, 1 | 2));
+        let up = active.as_ref().is_some_and(|(_, state)| *state == 2);
+        *self.active.borrow_mut() = active.as_ref().map(|(path, _)| path.clone());
+
+        self.updating.set(true);
+        self.switch.set_active(active.is_some() || self.pending_start.get());
+        self.updating.set(false);
+        if self.pending_start.get() {
+            return;
+        }
+
+        let ssid = profile.as_ref().and_then(|(_, settings)| settings_ssid(settings));
+        self.status.set_text(&match (&active, &ssid) {
+            (Some((_, 1)), Some(ssid)) => format!("starting {}…", ssid),
+            (Some(_), Some(ssid)) => format!("sharing as {}", ssid),
+            _ => "off".to_string(),
+        });
+
+        // the device's own network shouldn't show up as something to join
+        *self.wifi_page.hotspot_ssid.borrow_mut() = if active.is_some() { ssid } else { None };
+        self.wifi_page.regroup();
+        self.refresh_clients(up);
+    }
+
+    fn refresh_clients(&self, up: bool) {
+        if !up {
+            self.clients.set_visible(false);
+            return;
+        }
+        let interface = self
+            .device()
+            .and_then(|device| nm_prop::<String>(&self.nm, &device, NM_DEVICE_IFACE, "Interface").ok())
+            .unwrap_or_default();
+        let clients = hotspot_clients(&interface);
+        self.clients.set_text(&if clients.is_empty() { "no clients yet".to_string() } else { clients.join("\n") });
+        self.clients.set_visible(true);
+    }
+}
+
+fn open_hotspot_settings(hotspot: &Hotspot, window: &ApplicationWindow) {
+    let config = read_hotspot_config(&hotspot.nm);
+    let dialog = gtk4::Window::builder()
+        .title("Hotspot settings")
+        .modal(true)
+        .resizable(false)
+        .transient_for(window)
+        .build();
+    let dialog_box = GtkBox::new(Orientation::Vertical, 10);
+    dialog_box.set_margin_top(15);
+    dialog_box.set_margin_bottom(15);
+    dialog_box.set_margin_start(15);
+    dialog_box.set_margin_end(15);
+    let grid = gtk4::Grid::builder().row_spacing(8).column_spacing(20).build();
+    let ssid = gtk4::Entry::builder().text(config.ssid.as_str()).hexpand(true).build();
+    let password = gtk4::Entry::builder().text(config.password.as_str()).build();
+    let band = gtk4::DropDown::from_strings(&HOTSPOT_BAND_LABELS);
+    dropdown_select(&band, &HOTSPOT_BANDS, &config.band);
+    grid_row(&grid, 0, "Network name", &ssid);
+    grid_row(&grid, 1, "Password", &password);
+    grid_row(&grid, 2, "Band", &band);
+
+    let buttons = GtkBox::new(Orientation::Horizontal, 5);
+    buttons.set_halign(gtk4::Align::End);
+    let cancel = Button::with_label("Cancel");
+    let save = Button::with_label("Save");
+    buttons.append(&cancel);
+    buttons.append(&save);
+    dialog_box.append(&grid);
+    dialog_box.append(&buttons);
+    dialog.set_child(Some(&dialog_box));
+
+    let validate = {
+        let (ssid, password, save) = (ssid.clone(), password.clone(), save.clone());
+        move || save.set_sensitive(!ssid.text().is_empty() && ssid.text().len() <= 32 && WifiSecurity::Psk.is_valid_password(&password.text()))
+    };
+    validate();
+    let validate = Rc::new(validate);
+    for entry in [&ssid, &password] {
+        let validate = validate.clone();
+        entry.connect_changed(move |_| validate());
+    }
+
+    let dialog_clone = dialog.clone();
+    cancel.connect_clicked(move |_| dialog_clone.close());
+    let (hotspot, dialog_clone) = (hotspot.clone(), dialog.clone());
+    save.connect_clicked(move |_| {
+        let config = HotspotConfig {
+            ssid: ssid.text().to_string(),
+            password: password.text().to_string(),
+            band: dropdown_value(&band, &HOTSPOT_BANDS).to_string(),
+        };
+        let result = match hotspot_profile(&hotspot.nm) {
+            Some((path, settings)) => {
+                let uuid = settings_str(&settings, "connection", "uuid");
+                nm_call::<(), _>(&hotspot.nm, &path, NM_CONNECTION_IFACE, "Update", (config.settings(uuid),))
+            }
+            None => nm_call::<(dbus::Path<'static>,), _>(
+                &hotspot.nm,
+                &format!("{}/Settings", NM_PATH),
+                NM_SETTINGS_IFACE,
+                "AddConnection",
+                (config.settings(None),),
+            )
+            .map(|_| ()),
+        };
+        match result {
+            Ok(()) => {
+                show_notification(&hotspot.notif_box, "hotspot settings saved");
+                // a running hotspot only picks the change up when it is activated again
+                if hotspot.active.borrow().is_some() {
+                    hotspot.start();
+                }
+                dialog_clone.close();
+            }
+            Err(e) => show_notification(&hotspot.notif_box, &format!("hotspot not saved: {}", e.message().unwrap_or("NetworkManager refused"))),
+        }
+    });
+
+    dialog.present();
+}
+
+fn watch_hotspot_signals(hotspot: &Hotspot, radio_switch: &Switch, radio_updating: &Rc<std::cell::Cell<bool>>) {
+    use dbus::message::MatchRule;
+    let nm = &hotspot.nm;
+
+    let hotspot_clone = hotspot.clone();
+    let state = MatchRule::new_signal(NM_ACTIVE_IFACE, "StateChanged")
+        .with_sender(NM_BUS)
+        .with_namespaced_path(format!("{}/ActiveConnection", NM_PATH));
+    let _ = nm.add_match(state, move |(_, _): (u32, u32), _, _| {
+        hotspot_clone.reload();
+        true
+    });
+
+    // the Wi-Fi device leaving "unavailable" (20) after the radio came on
+    if let Some(device) = hotspot.device() {
+        let hotspot_clone = hotspot.clone();
+        let device_state = MatchRule::new_signal(NM_DEVICE_IFACE, "StateChanged").with_sender(NM_BUS).with_path(device);
+        let _ = nm.add_match(device_state, move |(new_state, _, _): (u32, u32, u32), _, _| {
+            if new_state > 20 {
+                hotspot_clone.device_ready();
+            }
+            true
+        });
+    }
+
+    // keep the radio switch honest when the hotspot (or anything else) turns the radio on or off;
+    // radio_updating keeps that from reaching nmcli again through the switch's state_set
+    let (radio_switch, radio_updating) = (radio_switch.clone(), radio_updating.clone());
+    let radio = MatchRule::new_signal(NM_PROPERTIES_IFACE, "PropertiesChanged").with_sender(NM_BUS).with_path(NM_PATH);
+    let _ = nm.add_match(radio, move |(iface, props, _): (String, dbus::arg::PropMap, Vec<String>), _, _| {
+        let enabled = dbus::arg::prop_cast::<bool>(&props, "WirelessEnabled").filter(|_| iface == NM_IFACE);
+        if let Some(enabled) = enabled.filter(|e| **e != radio_switch.is_active()) {
+            radio_updating.set(true);
+            radio_switch.set_active(*enabled);
+            radio_updating.set(false);
+        }
+        true
+    });
+
+    let hotspot_clone = hotspot.clone();
+    glib::timeout_add_seconds_local(5, move || {
+        if hotspot_clone.section.is_mapped() && hotspot_clone.active.borrow().is_some() {
+            hotspot_clone.refresh_clients(true);
+        }
+        glib::ControlFlow::Continue
+    });
+}
+
+// Wi-Fi QR codes ---------------------------------------------------------------------------------------------------------------------------------- //
+// The "WIFI:T:WPA;S:name;P:password;;" format phones understand. Codes are drawn by qrencode
+// and read back from images by zbarimg; the password only ever goes through their stdin/stdout.
+
+fn qr_escape(value: &str) -> String {
+    let mut escaped = String::new();
+    for c in value.chars() {
+        if matches!(c, '\\' | ';' | ',' | ':' | '"') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn wifi_qr_text(ssid: &str, security: WifiSecurity, password: &str, hidden: bool) -> String {
+    let kind = match security {
+        WifiSecurity::Wep => "WEP",
+        WifiSecurity::Sae => "SAE",
+        WifiSecurity::Open | WifiSecurity::Owe => "nopass",
+        _ => "WPA",
+    };
+    let mut text = format!("WIFI:T:{};S:{};", kind, qr_escape(ssid));
+    if kind != "nopass" {
+        text.push_str(&format!("P:{};", qr_escape(password)));
+    }
+    if hidden {
+        text.push_str("H:true;");
+    }
+    text.push(';');
+    text
+}
+
+struct WifiQr {
+    ssid: String,
+    security: WifiSecurity,
+    password: Option<String>,
+    hidden: bool,
+}
+
+fn parse_wifi_qr(text: &str) -> Option<WifiQr> {
+    let body = text.trim().strip_prefix("WIFI:")?;
+    let mut fields: HashMap<String, String> = HashMap::new();
+    let (mut field, mut escaped) = (String::new(), false);
+    for c in body.chars() {
+        match c {
+            _ if escaped => {
+                field.push(c);
+                escaped = false;
+            }
+            '\\' => escaped = true,
+            ';' => {
+                if let Some((key, value)) = field.split_once(':') {
+                    fields.insert(key.to_uppercase(), value.to_string());
+                }
+                field.clear();
+            }
+            _ => field.push(c),
+        }
+    }
+    let ssid = fields.get("S").filter(|s| !s.is_empty())?.clone();
+    let security = match fields.get("T").map(|t| t.to_uppercase()).as_deref() {
+        Some("WEP") => WifiSecurity::Wep,
+        Some("SAE") => WifiSecurity::Sae,
+        Some("WPA") => WifiSecurity::Psk,
+        _ => WifiSecurity::Open,
+    };
+    Some(WifiQr {
+        ssid,
+        security,
+        password: fields.get("P").filter(|p| !p.is_empty()).cloned(),
+        hidden: fields.get("H").is_some_and(|h| h.eq_ignore_ascii_case("true")),
+    })
+}
+
+/// PNG bytes of a QR code for `text`.
+async fn render_qr_png(text: &str) -> Result<glib::Bytes, String> {
+    let args = ["qrencode", "-t", "PNG", "-s", "8", "-m", "2", "-o", "-"];
+    let argv: Vec<&std::ffi::OsStr> = args.iter().map(std::ffi::OsStr::new).collect();
+    let flags = gtk4::gio::SubprocessFlags::STDIN_PIPE | gtk4::gio::SubprocessFlags::STDOUT_PIPE;
+    let process = gtk4::gio::Subprocess::newv(&argv, flags).map_err(|e| format!("qrencode: {}", e))?;
+    let input = glib::Bytes::from(text.as_bytes());
+    let (stdout, _) = process.communicate_future(Some(&input)).await.map_err(|e| format!("qrencode: {}", e))?;
+    match stdout {
+        Some(png) if process.is_successful() => Ok(png),
+        _ => Err("qrencode failed".to_string()),
+    }
+}
+
+async fn read_qr_image(path: &Path) -> Result<String, String> {
+    let argv = [std::ffi::OsStr::new("zbarimg"), "--quiet".as_ref(), "--raw".as_ref(), path.as_os_str()];
+    let process = gtk4::gio::Subprocess::newv(&argv, gtk4::gio::SubprocessFlags::STDOUT_PIPE).map_err(|e| format!("zbarimg: {}", e))?;
+    let (stdout, _) = process.communicate_utf8_future(None).await.map_err(|e| format!("zbarimg: {}", e))?;
+    // an image can hold more than one code, take the first Wi-Fi one
+    stdout
+        .unwrap_or_default()
+        .lines()
+        .find(|line| line.starts_with("WIFI:"))
+        .map(str::to_string)
+        .ok_or_else(|| "no Wi-Fi QR code found in the image".to_string())
+}
+
+/// The stored password of the profile the connected network is using, asking NetworkManager
+/// for its secrets.
+fn wifi_password(page: &WifiPage, network: &WifiNetwork) -> Option<String> {
+    let nm = &page.nm;
+    let ap = network.aps.iter().find(|ap| page.is_active(&ap.path))?;
+    let active = nm_prop::<dbus::Path<'static>>(nm, &ap.device, NM_DEVICE_IFACE, "ActiveConnection").ok().filter(|a| &**a != "/")?;
+    let path = nm_prop::<dbus::Path<'static>>(nm, &active, NM_ACTIVE_IFACE, "Connection").ok()?;
+    let (mut settings,) = nm_call::<(NmSettings,), _>(nm, &path, NM_CONNECTION_IFACE, "GetSettings", ()).ok()?;
+    merge_connection_secrets(nm, &path, &mut settings);
+    let wsec = "802-11-wireless-security";
+    settings_str(&settings, wsec, "psk").or_else(|| settings_str(&settings, wsec, "wep-key0"))
+}
+
+fn share_wifi_network(page: &WifiPage, network: &WifiNetwork, parent: &impl IsA<gtk4::Widget>) {
+    if !find_in_path("qrencode") {
+        show_notification(&page.notif_box, "install qrencode to share networks as QR codes");
+        return;
+    }
+    let password = match network.security {
+        WifiSecurity::Open | WifiSecurity::Owe => String::new(),
+        _ => match wifi_password(page, network) {
+            Some(password) => password,
+            None => {
+                show_notification(&page.notif_box, &format!("no stored password for {}", network.ssid));
+                return;
+            }
+        },
+    };
+    let text = wifi_qr_text(&network.ssid, network.security, &password, false);
+    let (notif_box, ssid) = (page.notif_box.clone(), network.ssid.clone());
+    let parent = parent.as_ref().clone();
+    glib::MainContext::default().spawn_local(async move {
+        let texture = render_qr_png(&text)
+            .await
+            .and_then(|png| gtk4::gdk::Texture::from_bytes(&png).map_err(|e| e.to_string()));
+        match texture {
+            Ok(texture) => show_wifi_qr(&texture, &ssid, &password, &parent),
+            Err(e) => show_notification(&notif_box, &format!("QR code not made: {}", e)),
+        }
+    });
+}
+
+fn show_wifi_qr(texture: &gtk4::gdk::Texture, network_ssid: &str, password: &str, parent: &gtk4::Widget) {
+    let window = gtk4::Window::builder().title("Share Wi-Fi").modal(true).resizable(false).build();
+    window.set_transient_for(parent.root().and_downcast::<gtk4::Window>().as_ref());
+    let dialog_box = GtkBox::new(Orientation::Vertical, 10);
+    dialog_box.set_margin_top(15);
+    dialog_box.set_margin_bottom(15);
+    dialog_box.set_margin_start(15);
+    dialog_box.set_margin_end(15);
+    let picture = gtk4::Picture::for_paintable(texture);
+    picture.set_size_request(300, 300);
+    let ssid = Label::new(Some(network_ssid));
+    ssid.set_css_classes(&["network_label"]);
+    let password_label = Label::new(Some(password));
+    password_label.set_selectable(true);
+    password_label.set_visible(false);
+    let show = gtk4::CheckButton::with_label("Show password");
+    show.set_visible(!password.is_empty());
+    let close = Button::with_label("Close");
+    close.set_halign(gtk4::Align::End);
+    dialog_box.append(&picture);
+    dialog_box.append(&ssid);
+    dialog_box.append(&show);
+    dialog_box.append(&password_label);
+    dialog_box.append(&close);
+    window.set_child(Some(&dialog_box));
+
+    show.connect_toggled(move |show| password_label.set_visible(show.is_active()));
+    let window_clone = window.clone();
+    close.connect_clicked(move |_| window_clone.close());
+    window.present();
+}
+
+fn join_from_qr_image(page: &WifiPage, window: &ApplicationWindow) {
+    if !find_in_path("zbarimg") {
+        show_notification(&page.notif_box, "install zbar (zbarimg) to read QR codes");
+        return;
+    }
+    let dialog = FileChooserDialog::new(
+        Some("Select a QR code image"),
+        Some(window),
+        FileChooserAction::Open,
+        &[("_Cancel", ResponseType::Cancel), ("_Open", ResponseType::Accept)],
+    );
+    dialog.set_css_classes(&["wall-dialog"]);
+    dialog.set_size_request(400, 800);
+    add_class_recursive(dialog.upcast_ref(), "wall-dialog");
+    let filter = gtk4::FileFilter::new();
+    filter.set_name(Some("Images"));
+    filter.add_mime_type("image/*");
+    dialog.add_filter(&filter);
+    if let Ok(home_dir) = env::var("HOME") {
+        let _ = dialog.set_current_folder(Some(&gtk4::gio::File::for_path(format!("{}/Pictures", home_dir))));
+    }
+
+    let page = page.clone();
+    dialog.connect_response(move |dialog, response| {
+        let source = dialog.file().and_then(|f| f.path());
+        dialog.close();
+        let (ResponseType::Accept, Some(source)) = (response, source) else {
+            return;
+        };
+        let page = page.clone();
+        glib::MainContext::default().spawn_local(async move {
+            match read_qr_image(&source).await.and_then(|text| parse_wifi_qr(&text).ok_or_else(|| "not a Wi-Fi QR code".to_string())) {
+                Ok(qr) => join_wifi_qr(&page, qr),
+                Err(e) => show_notification(&page.notif_box, &e),
+            }
+        });
+    });
+    dialog.show();
+}
+
+fn join_wifi_qr(page: &WifiPage, qr: WifiQr) {
+    if page.hotspot_ssid.borrow().is_some() {
+        show_notification(&page.notif_box, &format!("the hotspot is using the Wi-Fi adapter, turn it off to join {}", qr.ssid));
+        return;
+    }
+    // join the strongest access point when it is in range, else let NetworkManager look for it
+    let visible = page
+        .aps
+        .borrow()
+        .values()
+        .filter(|ap| ap.ssid == qr.ssid)
+        .max_by_key(|ap| ap.strength)
+        .cloned();
+    match visible {
+        Some(ap) => page.activate(ap, qr.password),
+        None => {
+            let mut settings = wifi_security_settings(qr.security, qr.password);
+            settings_set(&mut settings, "connection", "id", qr.ssid.clone());
+            settings_set(&mut settings, "connection", "type", "802-11-wireless".to_string());
+            settings_set(&mut settings, "802-11-wireless", "ssid", qr.ssid.clone().into_bytes());
+            settings_set(&mut settings, "802-11-wireless", "hidden", qr.hidden);
+            page.join_new_network(&qr.ssid, settings);
+        }
+    }
+}
+
+// Animated and video wallpapers --------------------------------------------------------------------------------------------------------------------- //
+// GIFs and animated WebPs go through swww like any other image, videos are played by mpvpaper.
+
+fn is_video_wallpaper(path: &Path) -> bool {
+    let ext = path.extension().and_then(|e| e.to_str()).map(|e| e.to_lowercase());
+    matches!(ext.as_deref(), Some("mp4" | "webm" | "mkv" | "mov" | "avi"))
+}
+
+// one second in skips fade-ins, short clips fall back to the first frame
+const VIDEO_FRAME_SEEKS: [&str; 2] = ["1", "0"];
+
+/// Where the sampled frame of a video lives: ~/.cache/calibrate/frames/<name>.png
+fn video_frame_path(path: &Path) -> Option<PathBuf> {
+    let home_dir = env::var("HOME").ok()?;
+    let cache_dir = PathBuf::from(format!("{}/.cache/calibrate/frames", home_dir));
+    fs::create_dir_all(&cache_dir).ok()?;
+    Some(cache_dir.join(format!("{}.png", path.file_name()?.to_string_lossy())))
+}
+
+/// The cached frame, if it is newer than the video.
+fn cached_video_frame(path: &Path) -> Option<PathBuf> {
+    let frame = video_frame_path(path)?;
+    let modified = |p: &Path| fs::metadata(p).and_then(|m| m.modified()).ok();
+    (frame.is_file() && modified(&frame) >= modified(path)).then_some(frame)
+}
+
+fn ffmpeg_frame_args(path: &Path, seek: &str, out: &Path) -> Vec<std::ffi::OsString> {
+    let mut args: Vec<std::ffi::OsString> = ["ffmpeg", "-nostdin", "-y", "-loglevel", "error", "-ss", seek, "-i"]
+        .iter()
+        .map(|a| a.into())
+        .collect();
+    args.push(path.into());
+    args.extend(["-frames:v".into(), "1".into()]);
+    args.push(out.into());
+    args
+}
+
+/// A frame near the start of a video. Blocks on ffmpeg, so only for worker threads;
+/// the UI goes through `video_frame_async`.
+fn video_frame(path: &Path) -> Option<PathBuf> {
+    if let Some(frame) = cached_video_frame(path) {
+        return Some(frame);
+    }
+    let frame = video_frame_path(path)?;
+    for seek in VIDEO_FRAME_SEEKS {
+        let args = ffmpeg_frame_args(path, seek, &frame);
+        let status = Command::new(&args[0]).args(&args[1..]).status();
+        if status.is_ok_and(|s| s.success()) && frame.is_file() {
+            return Some(frame);
+        }
+    }
+    None
+}
+
+/// Same as `video_frame`, but ffmpeg runs as a gio::Subprocess and `done` is called on the main loop.
+fn video_frame_async(path: PathBuf, done: impl FnOnce(Option<PathBuf>) + 'static) {
+    if let Some(frame) = cached_video_frame(&path) {
+        done(Some(frame));
+        return;
+    }
+    let Some(frame) = video_frame_path(&path) else {
+        done(None);
+        return;
+    };
+    glib::MainContext::default().spawn_local(async move {
+        // written under a temporary name, so a grid refresh never picks up a half written frame
+        let partial = frame.with_extension(format!("{}.png", glib::uuid_string_random()));
+        for seek in VIDEO_FRAME_SEEKS {
+            let args = ffmpeg_frame_args(&path, seek, &partial);
+            let argv: Vec<&std::ffi::OsStr> = args.iter().map(|a| a.as_os_str()).collect();
+            let Ok(process) = gtk4::gio::Subprocess::newv(&argv, gtk4::gio::SubprocessFlags::STDERR_SILENCE) else {
+                break;
+            };
+            if process.wait_check_future().await.is_ok() && fs::rename(&partial, &frame).is_ok() {
+                done(Some(frame));
+                return;
+            }
+        }
+        let _ = fs::remove_file(&partial);
+        done(None);
+    });
+}
+
+/// What to decode for thumbnails and brightness: the image itself or a sampled video frame.
+/// Samples missing frames with ffmpeg, use `cached_wallpaper_sample` where that must not block.
+fn wallpaper_sample(path: &Path) -> Option<PathBuf> {
+    if is_video_wallpaper(path) {
+        video_frame(path)
+    } else {
+        Some(path.to_path_buf())
+    }
+}
+
+fn cached_wallpaper_sample(path: &Path) -> Option<PathBuf> {
+    if is_video_wallpaper(path) {
+        cached_video_frame(path)
+    } else {
+        Some(path.to_path_buf())
+    }
+}
+
+fn scale_to_fit(pixbuf: &gtk4::gdk_pixbuf::Pixbuf, size: i32) -> Option<gtk4::gdk_pixbuf::Pixbuf> {
+    let (width, height) = (pixbuf.width(), pixbuf.height());
+    let factor = size as f64 / width.max(height).max(1) as f64;
+    pixbuf.scale_simple(
+        ((width as f64 * factor) as i32).max(1),
+        ((height as f64 * factor) as i32).max(1),
+        gtk4::gdk_pixbuf::InterpType::Bilinear,
+    )
+}
+
+fn animate_thumbnail(image: glib::WeakRef<Image>, iter: gtk4::gdk_pixbuf::PixbufAnimationIter, size: i32) {
+    let delay = iter.delay_time().unwrap_or(std::time::Duration::from_millis(100));
+    glib::timeout_add_local_once(delay.max(std::time::Duration::from_millis(20)), move || {
+        // stops once the thumbnail is gone
+        let Some(strong) = image.upgrade() else { return };
+        if strong.is_mapped() && iter.advance(std::time::SystemTime::now()) {
+            strong.set_from_pixbuf(scale_to_fit(&iter.pixbuf(), size).as_ref());
+        }
+        animate_thumbnail(image, iter, size);
+    });
+}
+
+fn wall_thumbnail(path: &Path, size: i32) -> Option<Image> {
+    let animation = if is_video_wallpaper(path) {
+        None
+    } else {
+        gtk4::gdk_pixbuf::PixbufAnimation::from_file(path).ok().filter(|a| !a.is_static_image())
+    };
+    if let Some(animation) = animation {
+        let iter = animation.iter(Some(std::time::SystemTime::now()));
+        let image = Image::from_pixbuf(scale_to_fit(&iter.pixbuf(), size).as_ref());
+        animate_thumbnail(image.downgrade(), iter, size);
+        return Some(image);
+    }
+    if is_video_wallpaper(path) && cached_video_frame(path).is_none() {
+        // filled in once ffmpeg has sampled the frame
+        let image = Image::new();
+        let weak = image.downgrade();
+        video_frame_async(path.to_path_buf(), move |frame| {
+            let (Some(image), Some(frame)) = (weak.upgrade(), frame) else { return };
+            if let Ok(pixbuf) = gtk4::gdk_pixbuf::Pixbuf::from_file_at_scale(frame, size, size, true) {
+                image.set_from_pixbuf(Some(&pixbuf));
+            }
+        });
+        return Some(image);
+    }
+    let sample = wallpaper_sample(path)?;
+    let pixbuf = gtk4::gdk_pixbuf::Pixbuf::from_file_at_scale(sample, size, size, true).ok()?;
+    Some(Image::from_pixbuf(Some(&pixbuf)))
+}
+
+/// Name and effective (rotation applied) resolution of each active monitor.
+fn monitor_modes() -> Vec<(String, i32, i32)> {
+    let Ok(output) = Command::new("hyprctl").arg("monitors").output() else {
+        return Vec::new();
+    };
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    let mut modes = Vec::new();
+    for block in stdout.split("Monitor ").skip(1) {
+        let name = block.split_whitespace().next().unwrap_or("").to_string();
+        let (mut width, mut height, mut transform) = (0, 0, 0);
+        for line in block.lines() {
+            let trimmed = line.trim();
+            let resolution = trimmed
+                .split_once(" at ")
+                .and_then(|(res, _)| res.split('@').next()?.split_once('x'));
+            if let Some((w, h)) = resolution {
+                width = w.trim().parse().unwrap_or(0);
+                height = h.trim().parse().unwrap_or(0);
+            }
+            if let Some(value) = trimmed.strip_prefix("transform:") {
+                transform = value.trim().parse().unwrap_or(0);
+            }
+        }
+        if transform % 2 == 1 {
+            std::mem::swap(&mut width, &mut height);
+        }
+        if !name.is_empty() && width > 0 && height > 0 {
+            modes.push((name, width, height));
+        }
+    }
+    modes
+}
+
+fn monitor_names() -> Vec<String> {
+    monitor_modes().into_iter().map(|(name, _, _)| name).collect()
+}
+
+/// Plays `path` on `output` ("ALL" for every monitor), replacing whatever mpvpaper showed there.
+fn set_video_wallpaper(path: &Path, output: &str) -> std::io::Result<()> {
+    stop_video_wallpapers(output);
+    Command::new("mpvpaper")
+        .args(["-o", "no-audio loop", output])
+        .arg(path)
+        .spawn()
+        .map(|_| ())
+}
+
+/// Stops mpvpaper on `output`, including one playing on "ALL" outputs; "ALL" stops every
+/// instance, whichever output it plays on.
+fn stop_video_wallpapers(output: &str) {
+    if output == "ALL" {
+        let _ = Command::new("pkill").args(["-x", "mpvpaper"]).status();
+    } else {
+        let pattern = format!("^mpvpaper -o no-audio loop ({}|ALL) ", output);
+        let _ = Command::new("pkill").arg("-f").arg(&pattern).status();
+    }
+}
+
+// Crop editor --------------------------------------------------------------------------------------------------------------------------------------- //
+
+/// Largest rectangle with the monitor's aspect ratio that fits the image, shrunk by `scale` around `center`.
+fn fit_crop(img_w: f64, img_h: f64, aspect: f64, scale: f64, center: (f64, f64)) -> CropRect {
+    let (full_w, full_h) = if img_w / img_h > aspect { (img_h * aspect, img_h) } else { (img_w, img_w / aspect) };
+    let w = full_w * scale / img_w;
+    let h = full_h * scale / img_h;
+    CropRect {
+        x: (center.0 - w / 2.0).clamp(0.0, 1.0 - w),
+        y: (center.1 - h / 2.0).clamp(0.0, 1.0 - h),
+        w,
+        h,
+    }
+}
+
+fn crop_wallpaper(source: &Path, crop: &CropRect, dest: &Path) -> Result<(), glib::Error> {
+    let pixbuf = gtk4::gdk_pixbuf::Pixbuf::from_file(source)?;
+    let (width, height) = (pixbuf.width(), pixbuf.height());
+    let x = ((crop.x * width as f64).round() as i32).clamp(0, width - 1);
+    let y = ((crop.y * height as f64).round() as i32).clamp(0, height - 1);
+    let w = ((crop.w * width as f64).round() as i32).clamp(1, width - x);
+    let h = ((crop.h * height as f64).round() as i32).clamp(1, height - y);
+    pixbuf.new_subpixbuf(x, y, w, h).savev(dest, "png", &[])
+}
+
+fn cropped_wallpaper_dir(home_dir: &str) -> PathBuf {
+    PathBuf::from(format!("{}/.cache/calibrate/cropped", home_dir))
+}
+
+/// Where the cropped copy of a wallpaper goes: ~/.cache/calibrate/cropped/<name>.png
+fn cropped_wallpaper_path(path: &Path) -> Option<PathBuf> {
+    let home_dir = env::var("HOME").ok()?;
+    Some(cropped_wallpaper_dir(&home_dir).join(format!("{}.png", path.file_name()?.to_string_lossy())))
+}
+
+/// wall.ctl records whatever swww was handed, map a cropped copy back to the wallpaper it was cut from.
+fn original_wallpaper(path: &Path, home_dir: &str) -> PathBuf {
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    match name.strip_suffix(".png") {
+        Some(original) if path.parent() == Some(cropped_wallpaper_dir(home_dir).as_path()) => {
+            PathBuf::from(format!("{}/.config/swww/cynage/{}", home_dir, original))
+        }
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Cropped copy of a wallpaper with stored crop metadata, regenerated in ~/.cache/calibrate/cropped.
+fn cropped_wallpaper(path: &Path, crop: &CropRect) -> Option<PathBuf> {
+    let dest = cropped_wallpaper_path(path)?;
+    fs::create_dir_all(dest.parent()?).ok()?;
+    match crop_wallpaper(path, crop, &dest) {
+        Ok(()) => Some(dest),
+        Err(e) => {
+            eprintln!("Failed to crop {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+fn open_crop_editor(path: &Path, notiv_boxxy: &GtkBox, wall_state: &WallState) {
+    let animated = gtk4::gdk_pixbuf::PixbufAnimation::from_file(path).is_ok_and(|a| !a.is_static_image());
+    if is_video_wallpaper(path) || animated {
+        show_notification(notiv_boxxy, "only still images can be cropped");
+        return;
+    }
+    let Ok(source) = gtk4::gdk_pixbuf::Pixbuf::from_file(path) else {
+        show_notification(notiv_boxxy, "could not open the image");
+        return;
+    };
+    let (img_w, img_h) = (source.width() as f64, source.height() as f64);
+    let Some(preview) = scale_to_fit(&source, 1200) else { return };
+    drop(source);
+
+    let mut modes = monitor_modes();
+    if modes.is_empty() {
+        modes.push(("1920x1080".to_string(), 1920, 1080));
+    }
+    let modes = Rc::new(modes);
+    let aspect_of = {
+        let modes = modes.clone();
+        move |i: u32| {
+            let (_, w, h) = &modes[(i as usize).min(modes.len() - 1)];
+            *w as f64 / *h as f64
+        }
+    };
+
+    let filename = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+    let stored = wall_state.meta.borrow().get(&filename).and_then(|e| e.crop);
+    let crop = Rc::new(RefCell::new(
+        stored.unwrap_or_else(|| fit_crop(img_w, img_h, aspect_of(0), 1.0, (0.5, 0.5))),
+    ));
+
+    let window = gtk4::Window::builder()
+        .title(format!("Crop {}", filename))
+        .modal(true)
+        .default_width(1000)
+        .default_height(700)
+        .build();
+    if let Some(parent) = wall_state.scroller.root().and_downcast::<gtk4::Window>() {
+        window.set_transient_for(Some(&parent));
+    }
+
+    let editor_box = GtkBox::new(Orientation::Vertical, 10);
+    let area = gtk4::DrawingArea::new();
+    area.set_hexpand(true);
+    area.set_vexpand(true);
+
+    // where the preview lands inside the drawing area: (offset x, offset y, scale)
+    let layout = {
+        let (pw, ph) = (preview.width() as f64, preview.height() as f64);
+        move |width: f64, height: f64| {
+            let s = (width / pw).min(height / ph);
+            ((width - pw * s) / 2.0, (height - ph * s) / 2.0, s)
+        }
+    };
+
+    let crop_draw = crop.clone();
+    let layout_draw = layout;
+    let preview_draw = preview.clone();
+    area.set_draw_func(move |_, cr, width, height| {
+        let (ox, oy, s) = layout_draw(width as f64, height as f64);
+        let (pw, ph) = (preview_draw.width() as f64 * s, preview_draw.height() as f64 * s);
+
+        cr.save().ok();
+        cr.translate(ox, oy);
+        cr.scale(s, s);
+        cr.set_source_pixbuf(&preview_draw, 0.0, 0.0);
+        let _ = cr.paint();
+        cr.restore().ok();
+
+        let c = *crop_draw.borrow();
+        let (cx, cy, cw, ch) = (ox + c.x * pw, oy + c.y * ph, c.w * pw, c.h * ph);
+
+        // dim everything outside the crop
+        cr.set_fill_rule(gtk4::cairo::FillRule::EvenOdd);
+        cr.rectangle(ox, oy, pw, ph);
+        cr.rectangle(cx, cy, cw, ch);
+        cr.set_source_rgba(0.0, 0.0, 0.0, 0.6);
+        let _ = cr.fill();
+
+        cr.rectangle(cx, cy, cw, ch);
+        cr.set_source_rgb(5.0 / 255.0, 148.0 / 255.0, 122.0 / 255.0);
+        cr.set_line_width(2.0);
+        let _ = cr.stroke();
+    });
+
+    // drag the crop around
+    let drag = gtk4::GestureDrag::new();
+    let drag_start = Rc::new(RefCell::new(*crop.borrow()));
+    let crop_begin = crop.clone();
+    let drag_start_begin = drag_start.clone();
+    drag.connect_drag_begin(move |_, _, _| {
+        *drag_start_begin.borrow_mut() = *crop_begin.borrow();
+    });
+    let crop_update = crop.clone();
+    let area_update = area.clone();
+    let layout_update = layout;
+    let (pw, ph) = (preview.width() as f64, preview.height() as f64);
+    drag.connect_drag_update(move |_, dx, dy| {
+        let (_, _, s) = layout_update(area_update.width() as f64, area_update.height() as f64);
+        let start = *drag_start.borrow();
+        let mut c = crop_update.borrow_mut();
+        c.x = (start.x + dx / (pw * s)).clamp(0.0, 1.0 - c.w);
+        c.y = (start.y + dy / (ph * s)).clamp(0.0, 1.0 - c.h);
+        drop(c);
+        area_update.queue_draw();
+    });
+    area.add_controller(drag);
+
+    let controls = GtkBox::new(Orientation::Horizontal, 5);
+    let mode_labels: Vec<String> = modes.iter().map(|(name, w, h)| format!("{} ({}x{})", name, w, h)).collect();
+    let mode_refs: Vec<&str> = mode_labels.iter().map(|s| s.as_str()).collect();
+    let monitor_drop = gtk4::DropDown::from_strings(&mode_refs);
+    let initial_scale = {
+        let c = *crop.borrow();
+        let full = fit_crop(img_w, img_h, c.w * img_w / (c.h * img_h), 1.0, (0.5, 0.5));
+        (c.w / full.w).clamp(0.1, 1.0)
+    };
+    let zoom = gtk4::Scale::with_range(Orientation::Horizontal, 0.1, 1.0, 0.01);
+    zoom.set_value(initial_scale);
+    zoom.set_hexpand(true);
+
+    let resize = {
+        let crop = crop.clone();
+        let area = area.clone();
+        let zoom = zoom.clone();
+        let monitor_drop = monitor_drop.clone();
+        move || {
+            let c = *crop.borrow();
+            let center = (c.x + c.w / 2.0, c.y + c.h / 2.0);
+            *crop.borrow_mut() = fit_crop(img_w, img_h, aspect_of(monitor_drop.selected()), zoom.value(), center);
+            area.queue_draw();
+        }
+    };
+    let resize_clone = resize.clone();
+    zoom.connect_value_changed(move |_| resize_clone());
+    monitor_drop.connect_selected_notify(move |_| resize());
+
+    let save_copy = Button::with_label("Save cropped copy");
+    let save_meta = Button::with_label("Crop when set");
+    let clear_meta = Button::with_label("Clear crop");
+    let cancel = Button::with_label("Cancel");
+    controls.append(&Label::new(Some("Monitor")));
+    controls.append(&monitor_drop);
+    controls.append(&Label::new(Some("Size")));
+    controls.append(&zoom);
+    controls.append(&save_copy);
+    controls.append(&save_meta);
+    controls.append(&clear_meta);
+    controls.append(&cancel);
+
+    editor_box.append(&area);
+    editor_box.append(&controls);
+    window.set_child(Some(&editor_box));
+
+    let source_path = path.to_path_buf();
+    let crop_clone = crop.clone();
+    let window_clone = window.clone();
+    let notiv_clone = notiv_boxxy.clone();
+    let wall_state_clone = wall_state.clone();
+    let modes_clone = modes.clone();
+    let monitor_drop_clone = monitor_drop.clone();
+    save_copy.connect_clicked(move |_| {
+        let (monitor, _, _) = &modes_clone[(monitor_drop_clone.selected() as usize).min(modes_clone.len() - 1)];
+        let stem = source_path.file_stem().unwrap_or_default().to_string_lossy();
+        let dest = source_path.with_file_name(format!("{}-{}.png", stem, monitor));
+        match crop_wallpaper(&source_path, &crop_clone.borrow(), &dest) {
+            Ok(()) => show_notification(&notiv_clone, &format!("saved {}", dest.file_name().unwrap_or_default().to_string_lossy())),
+            Err(e) => show_notification(&notiv_clone, &format!("could not save crop: {}", e)),
+        }
+        window_clone.close();
+        add_walls_to_grid(&notiv_clone, &wall_state_clone);
+    });
+
+    for (button, keep) in [(&save_meta, true), (&clear_meta, false)] {
+        let crop_clone = crop.clone();
+        let window_clone = window.clone();
+        let notiv_clone = notiv_boxxy.clone();
+        let wall_state_clone = wall_state.clone();
+        let filename = filename.clone();
+        button.connect_clicked(move |_| {
+            {
+                let mut meta = wall_state_clone.meta.borrow_mut();
+                meta.entry(filename.clone()).or_default().crop = keep.then(|| *crop_clone.borrow());
+                save_wall_meta(&meta);
+            }
+            show_notification(&notiv_clone, if keep { "crop saved, applied when the wallpaper is set" } else { "crop cleared" });
+            window_clone.close();
+        });
+    }
+
+    let window_clone = window.clone();
+    cancel.connect_clicked(move |_| window_clone.close());
+
+    window.present();
+}
+
+// Color wallpapers ------------------------------------------------------------------------------------------------------------------------------- //
+
+#[derive(Clone, Copy, PartialEq)]
+enum ColorFill {
+    Solid,
+    Linear,
+    Radial,
+}
+
+impl ColorFill {
+    fn from_index(index: u32) -> Self {
+        match index {
+            1 => ColorFill::Linear,
+            2 => ColorFill::Radial,
+            _ => ColorFill::Solid,
+        }
+    }
+}
+
+fn rgba_hex(color: &gdk::RGBA) -> String {
+    format!(
+        "{:02x}{:02x}{:02x}",
+        (color.red() * 255.0).round() as u8,
+        (color.green() * 255.0).round() as u8,
+        (color.blue() * 255.0).round() as u8
+    )
+}
+
+/// GNOME's accent-color setting, falling back to the teal the calibrate theme uses.
+fn accent_color() -> gdk::RGBA {
+    let output = Command::new("gsettings")
+        .args(["get", "org.gnome.desktop.interface", "accent-color"])
+        .output();
+    let name = output
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().trim_matches('\'').to_string())
+        .unwrap_or_default();
+    // libadwaita's accent palette
+    let hex = match name.as_str() {
+        "blue" => "#3584e4",
+        "teal" => "#2190a4",
+        "green" => "#3a944a",
+        "yellow" => "#c88800",
+        "orange" => "#ed5b00",
+        "red" => "#e62d42",
+        "pink" => "#d56199",
+        "purple" => "#9141ac",
+        "slate" => "#6f8396",
+        _ => "#05947a",
+    };
+    gdk::RGBA::parse(hex).unwrap_or(gdk::RGBA::BLACK)
+}
+
+fn render_color_wallpaper(fill: ColorFill, from: &gdk::RGBA, to: &gdk::RGBA, angle: f64, width: i32, height: i32) -> gtk4::gdk_pixbuf::Pixbuf {
+    let (w, h) = (width.max(1) as usize, height.max(1) as usize);
+    let (dx, dy) = (angle.to_radians().cos(), angle.to_radians().sin());
+    let (cx, cy) = (w as f64 / 2.0, h as f64 / 2.0);
+    // distance from the centre to the edge along the gradient, so t runs 0..1 corner to corner
+    let linear_extent = (cx * dx.abs() + cy * dy.abs()).max(1.0);
+    let radial_extent = (cx * cx + cy * cy).sqrt().max(1.0);
+    let from = [from.red() as f64, from.green() as f64, from.blue() as f64];
+    let to = [to.red() as f64, to.green() as f64, to.blue() as f64];
+
+    let mut data = vec![0u8; w * h * 3];
+    for y in 0..h {
+        for x in 0..w {
+            let (px, py) = (x as f64 + 0.5 - cx, y as f64 + 0.5 - cy);
+            let t = match fill {
+                ColorFill::Solid => 0.0,
+                ColorFill::Linear => ((px * dx + py * dy) / linear_extent + 1.0) / 2.0,
+                ColorFill::Radial => (px * px + py * py).sqrt() / radial_extent,
+            }
+            .clamp(0.0, 1.0);
+            let offset = (y * w + x) * 3;
+            for c in 0..3 {
+                data[offset + c] = ((from[c] + (to[c] - from[c]) * t) * 255.0).round() as u8;
+            }
+        }
+    }
+
+    gtk4::gdk_pixbuf::Pixbuf::from_bytes(
+        &glib::Bytes::from_owned(data),
+        gtk4::gdk_pixbuf::Colorspace::Rgb,
+        false,
+        8,
+        w as i32,
+        h as i32,
+        (w * 3) as i32,
+    )
+}
+
+fn build_color_wallpaper_page(notiv_boxxy: &GtkBox, wall_state: &WallState) -> GtkBox {
+    let page = GtkBox::new(Orientation::Horizontal, 20);
+    page.set_margin_top(10);
+
+    let controls = gtk4::Grid::builder().column_spacing(10).row_spacing(8).valign(gtk4::Align::Start).build();
+    let fill = gtk4::DropDown::from_strings(&["Solid", "Linear gradient", "Radial gradient"]);
+    let from = gtk4::ColorButton::with_rgba(&accent_color());
+    let to = gtk4::ColorButton::with_rgba(&gdk::RGBA::BLACK);
+    let angle = gtk4::Scale::with_range(Orientation::Horizontal, 0.0, 360.0, 15.0);
+    angle.set_value(90.0);
+    angle.set_draw_value(true);
+    angle.set_size_request(200, -1);
+    let use_accent = Button::with_label("Use accent color");
+    let apply = Button::with_label("Set as wallpaper");
+
+    for (row, (label, widget)) in [
+        ("Fill", fill.clone().upcast::<gtk4::Widget>()),
+        ("Color", from.clone().upcast()),
+        ("Second color", to.clone().upcast()),
+        ("Angle", angle.clone().upcast()),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let label = Label::new(Some(label));
+        label.set_halign(gtk4::Align::Start);
+        controls.attach(&label, 0, row as i32, 1, 1);
+        controls.attach(&widget, 1, row as i32, 1, 1);
+    }
+    controls.attach(&use_accent, 0, 4, 1, 1);
+    controls.attach(&apply, 1, 4, 1, 1);
+
+    let preview = gtk4::Picture::new();
+    preview.set_hexpand(true);
+    preview.set_size_request(320, 180);
+
+    page.append(&controls);
+    page.append(&preview);
+
+    let (preview_w, preview_h) = monitor_modes()
+        .first()
+        .map(|(_, w, h)| (320, 320 * h / (*w).max(1)))
+        .unwrap_or((320, 180));
+    let update_preview = {
+        let fill = fill.clone();
+        let from = from.clone();
+        let to = to.clone();
+        let angle = angle.clone();
+        let preview = preview.clone();
+        move || {
+            let kind = ColorFill::from_index(fill.selected());
+            to.set_sensitive(kind != ColorFill::Solid);
+            angle.set_sensitive(kind == ColorFill::Linear);
+            let pixbuf = render_color_wallpaper(kind, &from.rgba(), &to.rgba(), angle.value(), preview_w, preview_h);
+            preview.set_paintable(Some(&gtk4::gdk::Texture::for_pixbuf(&pixbuf)));
+        }
+    };
+    update_preview();
+    let update = update_preview.clone();
+    fill.connect_selected_notify(move |_| update());
+    let update = update_preview.clone();
+    from.connect_color_set(move |_| update());
+    let update = update_preview.clone();
+    to.connect_color_set(move |_| update());
+    let update = update_preview.clone();
+    angle.connect_value_changed(move |_| update());
+
+    // accent for the first stop, a darker shade of it for the second
+    let from_clone = from.clone();
+    let to_clone = to.clone();
+    use_accent.connect_clicked(move |_| {
+        let accent = accent_color();
+        from_clone.set_rgba(&accent);
+        to_clone.set_rgba(&gdk::RGBA::new(accent.red() * 0.35, accent.green() * 0.35, accent.blue() * 0.35, 1.0));
+        update_preview();
+    });
+
+    let notiv_clone = notiv_boxxy.clone();
+    let wall_state_clone = wall_state.clone();
+    apply.connect_clicked(move |_| {
+        let kind = ColorFill::from_index(fill.selected());
+        let (from, to, angle) = (from.rgba(), to.rgba(), angle.value());
+        // one image at the largest monitor's resolution, swww scales it down to fill the others
+        let (width, height) = monitor_modes()
+            .into_iter()
+            .map(|(_, width, height)| (width, height))
+            .max_by_key(|(width, height)| width * height)
+            .unwrap_or((1920, 1080));
+
+        let home_dir = env::var("HOME").unwrap_or_default();
+        let wallpaper_dir = PathBuf::from(format!("{}/.config/swww/cynage", home_dir));
+        let _ = fs::create_dir_all(&wallpaper_dir);
+        let stem = match kind {
+            ColorFill::Solid => format!("color-solid-{}", rgba_hex(&from)),
+            ColorFill::Linear => format!("color-linear-{}-{}-{}", rgba_hex(&from), rgba_hex(&to), angle as i32),
+            ColorFill::Radial => format!("color-radial-{}-{}", rgba_hex(&from), rgba_hex(&to)),
+        };
+
+        let filename = format!("{}-{}x{}.png", stem, width, height);
+        let dest = wallpaper_dir.join(&filename);
+        if !dest.exists() {
+            let pixbuf = render_color_wallpaper(kind, &from, &to, angle, width, height);
+            if let Err(e) = pixbuf.savev(&dest, "png", &[]) {
+                show_notification(&notiv_clone, &format!("could not save {}: {}", filename, e));
+                return;
+            }
+        }
+        set_desktop_wallpaper(&filename, &notiv_clone, &wall_state_clone);
+        add_walls_to_grid(&notiv_clone, &wall_state_clone);
+    });
+
+    page
+}
+
+// Lock screen ------------------------------------------------------------------------------------------------------------------------------------ //
+// Only the keys calibrate manages inside hyprlock's `background { }` blocks are touched,
+// everything else in hyprlock.conf is written back as it was.
+
+fn hyprlock_conf_path() -> PathBuf {
+    let home_dir = env::var("HOME").unwrap_or_default();
+    PathBuf::from(format!("{}/.config/hypr/hyprlock.conf", home_dir))
+}
+
+fn is_background_block_start(line: &str) -> bool {
+    let trimmed = line.trim();
+    trimmed.starts_with("background") && trimmed["background".len()..].trim_start().starts_with('{')
+}
+
+/// key = value pairs of the first background block.
+fn read_hyprlock_background(conf: &str) -> HashMap<String, String> {
+    let mut values = HashMap::new();
+    let mut inside = false;
+    for line in conf.lines() {
+        let trimmed = line.trim();
+        if !inside {
+            inside = is_background_block_start(line);
+            continue;
+        }
+        if trimmed.starts_with('}') {
+            break;
+        }
+        if let Some((key, value)) = trimmed.split_once('=') {
+            values.insert(key.trim().to_string(), value.trim().to_string());
+        }
+    }
+    values
+}
+
+/// Sets `settings` in every background block, adding one at the end when there is none.
+fn set_hyprlock_background(conf: &str, settings: &[(&str, String)]) -> String {
+    let mut out = String::new();
+    let mut inside = false;
+    let mut found_block = false;
+    let mut seen: Vec<&str> = Vec::new();
+    let mut indent = String::from("    ");
+
+    for line in conf.lines() {
+        let trimmed = line.trim();
+        if !inside {
+            if is_background_block_start(line) {
+                inside = true;
+                found_block = true;
+                seen.clear();
+            }
+            out.push_str(line);
+            out.push('\n');
+            continue;
+        }
+
+        if trimmed.starts_with('}') {
+            for (key, value) in settings {
+                if !seen.contains(key) {
+                    out.push_str(&format!("{}{} = {}\n", indent, key, value));
+                }
+            }
+            inside = false;
+            out.push_str(line);
+            out.push('\n');
+            continue;
+        }
+
+        let key = trimmed.split_once('=').map(|(k, _)| k.trim());
+        match key.and_then(|k| settings.iter().find(|(name, _)| *name == k)) {
+            Some((name, value)) => {
+                indent = line[..line.len() - line.trim_start().len()].to_string();
+                out.push_str(&format!("{}{} = {}\n", indent, name, value));
+                seen.push(name);
+            }
+            None => {
+                out.push_str(line);
+                out.push('\n');
+            }
+        }
+    }
+
+    if !found_block {
+        if !out.is_empty() && !out.ends_with("\n\n") {
+            out.push('\n');
+        }
+        out.push_str("background {\n    monitor =\n");
+        for (key, value) in settings {
+            out.push_str(&format!("    {} = {}\n", key, value));
+        }
+        out.push_str("}\n");
+    }
+    out
+}
+
+/// Rough stand-in for hyprlock's blur: `passes` rounds of a box blur, then brightness.
+fn blur_preview(pixbuf: &gtk4::gdk_pixbuf::Pixbuf, passes: u32, size: u32, brightness: f64) -> gtk4::gdk_pixbuf::Pixbuf {
+    let width = pixbuf.width() as usize;
+    let height = pixbuf.height() as usize;
+    let rowstride = pixbuf.rowstride() as usize;
+    let n_channels = pixbuf.n_channels() as usize;
+    let mut data = pixbuf.read_pixel_bytes().to_vec();
+    let radius = size as usize;
+
+    let blur_line = |data: &mut Vec<u8>, horizontal: bool| {
+        let (lines, len) = if horizontal { (height, width) } else { (width, height) };
+        let mut line = vec![0u32; len];
+        for l in 0..lines {
+            let index = |i: usize, c: usize| {
+                if horizontal { l * rowstride + i * n_channels + c } else { i * rowstride + l * n_channels + c }
+            };
+            for c in 0..n_channels.min(3) {
+                for (i, value) in line.iter_mut().enumerate() {
+                    *value = data.get(index(i, c)).copied().unwrap_or(0) as u32;
+                }
+                let mut sum: u32 = 0;
+                let mut count: u32 = 0;
+                for value in line.iter().take(radius.min(len)) {
+                    sum += value;
+                    count += 1;
+                }
+                for i in 0..len {
+                    if i + radius < len {
+                        sum += line[i + radius];
+                        count += 1;
+                    }
+                    if i > radius {
+                        sum -= line[i - radius - 1];
+                        count -= 1;
+                    }
+                    if let Some(px) = data.get_mut(index(i, c)) {
+                        *px = (sum / count.max(1)) as u8;
+                    }
+                }
+            }
+        }
+    };
+
+    if radius > 0 {
+        for _ in 0..passes {
+            blur_line(&mut data, true);
+            blur_line(&mut data, false);
+        }
+    }
+
+    for y in 0..height {
+        for x in 0..width {
+            for c in 0..n_channels.min(3) {
+                if let Some(px) = data.get_mut(y * rowstride + x * n_channels + c) {
+                    *px = (*px as f64 * brightness).clamp(0.0, 255.0) as u8;
+                }
+            }
+        }
+    }
+
+    gtk4::gdk_pixbuf::Pixbuf::from_bytes(
+        &glib::Bytes::from_owned(data),
+        pixbuf.colorspace(),
+        pixbuf.has_alpha(),
+        pixbuf.bits_per_sample(),
+        pixbuf.width(),
+        pixbuf.height(),
+        pixbuf.rowstride(),
+    )
+}
+
+fn open_lock_screen_dialog(filename: &str, also_desktop: bool, notiv_boxxy: &GtkBox, wall_state: &WallState) {
+    let home_dir = env::var("HOME").unwrap_or_default();
+    let target_path = PathBuf::from(format!("{}/.config/swww/cynage/{}", home_dir, filename));
+    if is_video_wallpaper(&target_path) {
+        show_notification(notiv_boxxy, "hyprlock can only show still images");
+        return;
+    }
+    // hyprlock gets the same cropped copy swww would
+    let crop = wall_state.meta.borrow().get(filename).and_then(|e| e.crop);
+    let lock_path = crop
+        .and_then(|c| cropped_wallpaper(&target_path, &c))
+        .unwrap_or_else(|| target_path.clone());
+    let Ok(preview_source) = gtk4::gdk_pixbuf::Pixbuf::from_file_at_scale(&lock_path, 640, 400, true) else {
+        show_notification(notiv_boxxy, "could not open the image");
+        return;
+    };
+
+    let conf = fs::read_to_string(hyprlock_conf_path()).unwrap_or_default();
+    let current = read_hyprlock_background(&conf);
+    let current_num = |key: &str, default: f64| current.get(key).and_then(|v| v.parse().ok()).unwrap_or(default);
+
+    let window = gtk4::Window::builder()
+        .title(if also_desktop { "Set as desktop and lock screen" } else { "Set as lock screen" })
+        .modal(true)
+        .build();
+    if let Some(parent) = wall_state.scroller.root().and_downcast::<gtk4::Window>() {
+        window.set_transient_for(Some(&parent));
+    }
+
+    let dialog_box = GtkBox::new(Orientation::Vertical, 10);
+    let preview = gtk4::Picture::new();
+    preview.set_size_request(640, 400);
+
+    let settings_grid = gtk4::Grid::builder().column_spacing(10).row_spacing(5).build();
+    let passes = gtk4::Scale::with_range(Orientation::Horizontal, 0.0, 4.0, 1.0);
+    let size = gtk4::Scale::with_range(Orientation::Horizontal, 0.0, 15.0, 1.0);
+    let brightness = gtk4::Scale::with_range(Orientation::Horizontal, 0.0, 1.5, 0.05);
+    passes.set_value(current_num("blur_passes", 2.0));
+    size.set_value(current_num("blur_size", 7.0));
+    brightness.set_value(current_num("brightness", 0.8));
+    for (row, (label, scale)) in [("Blur passes", &passes), ("Blur size", &size), ("Brightness", &brightness)].into_iter().enumerate() {
+        let label = Label::new(Some(label));
+        label.set_halign(gtk4::Align::Start);
+        scale.set_draw_value(true);
+        scale.set_hexpand(true);
+        settings_grid.attach(&label, 0, row as i32, 1, 1);
+        settings_grid.attach(scale, 1, row as i32, 1, 1);
+    }
+
+    let update_preview = {
+        let preview = preview.clone();
+        let passes = passes.clone();
+        let size = size.clone();
+        let brightness = brightness.clone();
+        move || {
+            let blurred = blur_preview(&preview_source, passes.value() as u32, size.value() as u32, brightness.value());
+            preview.set_paintable(Some(&gtk4::gdk::Texture::for_pixbuf(&blurred)));
+        }
+    };
+    update_preview();
+    for scale in [&passes, &size, &brightness] {
+        let update_preview = update_preview.clone();
+        scale.connect_value_changed(move |_| update_preview());
+    }
+
+    let buttons = GtkBox::new(Orientation::Horizontal, 5);
+    buttons.set_halign(gtk4::Align::End);
+    let cancel = Button::with_label("Cancel");
+    let apply = Button::with_label("Apply");
+    buttons.append(&cancel);
+    buttons.append(&apply);
+
+    dialog_box.append(&preview);
+    dialog_box.append(&settings_grid);
+    dialog_box.append(&buttons);
+    window.set_child(Some(&dialog_box));
+
+    let window_clone = window.clone();
+    cancel.connect_clicked(move |_| window_clone.close());
+
+    let window_clone = window.clone();
+    let notiv_clone = notiv_boxxy.clone();
+    let wall_state_clone = wall_state.clone();
+    let filename = filename.to_string();
+    apply.connect_clicked(move |_| {
+        let settings = [
+            ("path", lock_path.to_string_lossy().to_string()),
+            ("blur_passes", format!("{}", passes.value() as u32)),
+            ("blur_size", format!("{}", size.value() as u32)),
+            ("brightness", format!("{:.2}", brightness.value())),
+        ];
+        let conf = fs::read_to_string(hyprlock_conf_path()).unwrap_or_default();
+        let updated = set_hyprlock_background(&conf, &settings);
+        window_clone.close();
+        if let Err(e) = fs::write(hyprlock_conf_path(), updated) {
+            show_notification(&notiv_clone, &format!("could not write hyprlock.conf: {}", e));
+            return;
+        }
+        if also_desktop {
+            set_desktop_wallpaper(&filename, &notiv_clone, &wall_state_clone);
+        } else {
+            show_notification(&notiv_clone, "lock screen background changed");
+        }
+    });
+
+    window.present();
+}
+
+// Wallpaper metadata ------------------------------------------------------------------------------------------------------------------------------- //
+// Favourites, tags and cached image stats live in ~/.config/swww/wallmeta.ctl, one wallpaper per line:
+//     <file name>|<favourite 0/1>|<tag,tag>|<lightness>|<dominant hue>|<crop x,y,w,h>
+
+/// Part of an image to show, as fractions of its width and height.
+#[derive(Clone, Copy)]
+struct CropRect {
+    x: f64,
+    y: f64,
+    w: f64,
+    h: f64,
+}
+
+#[derive(Clone, Default)]
+struct WallMetaEntry {
+    favourite: bool,
+    tags: Vec<String>,
+    lightness: Option<f64>,
+    hue: Option<f64>,
+    crop: Option<CropRect>,
+}
+
+fn wall_meta_path() -> PathBuf {
+    let home_dir = env::var("HOME").unwrap_or_default();
+    PathBuf::from(format!("{}/.config/swww/wallmeta.ctl", home_dir))
+}
+
+fn load_wall_meta() -> HashMap<String, WallMetaEntry> {
+    let mut meta = HashMap::new();
+    let content = fs::read_to_string(wall_meta_path()).unwrap_or_default();
+    for line in content.lines() {
+        // split from the right so '|' in file names survives
+        let mut fields: Vec<&str> = line.rsplitn(6, '|').collect();
+        fields.reverse();
+        if fields.len() < 5 {
+            continue;
+        }
+        let crop: Vec<f64> = fields
+            .get(5)
+            .map(|c| c.split(',').filter_map(|v| v.parse().ok()).collect())
+            .unwrap_or_default();
+        let entry = WallMetaEntry {
+            favourite: fields[1] == "1",
+            tags: parse_wall_tags(fields[2]),
+            lightness: fields[3].parse().ok(),
+            hue: fields[4].parse().ok(),
+            crop: match crop[..] {
+                [x, y, w, h] if w > 0.0 && h > 0.0 => Some(CropRect { x, y, w, h }),
+                _ => None,
+            },
+        };
+        meta.insert(fields[0].to_string(), entry);
+    }
+    meta
+}
+
+/// Tags are split on ',' and the line on '|', so neither can be part of a tag.
+fn parse_wall_tags(text: &str) -> Vec<String> {
+    text.split(',')
+        .map(|t| t.replace('|', "").trim().to_string())
+        .filter(|t| !t.is_empty())
+        .collect()
+}
+
+/// Drops entries for wallpapers that were removed or trashed since the last run.
+fn load_pruned_wall_meta() -> HashMap<String, WallMetaEntry> {
+    let mut meta = load_wall_meta();
+    let home_dir = env::var("HOME").unwrap_or_default();
+    let wallpaper_dir = PathBuf::from(format!("{}/.config/swww/cynage", home_dir));
+    let before = meta.len();
+    meta.retain(|name, _| wallpaper_dir.join(name).exists());
+    if meta.len() != before {
+        save_wall_meta(&meta);
+    }
+    meta
+}
+
+fn forget_wall_meta(wall_state: &WallState, paths: &[PathBuf]) {
+    let mut meta = wall_state.meta.borrow_mut();
+    let before = meta.len();
+    for path in paths {
+        if let Some(name) = path.file_name() {
+            meta.remove(&*name.to_string_lossy());
+            // a file imported under this name later gets its stats worked out again
+            wall_state.stats_tried.borrow_mut().remove(&*name.to_string_lossy());
+        }
+    }
+    if meta.len() != before {
+        save_wall_meta(&meta);
+    }
+}
+
+fn save_wall_meta(meta: &HashMap<String, WallMetaEntry>) {
+    let mut names: Vec<&String> = meta.keys().collect();
+    names.sort();
+
+    let mut content = String::new();
+    for name in names {
+        let entry = &meta[name];
+        content.push_str(&format!(
+            "{}|{}|{}|{}|{}|{}\n",
+            name,
+            if entry.favourite { 1 } else { 0 },
+            entry.tags.iter().map(|t| t.replace('|', "")).collect::<Vec<_>>().join(","),
+            entry.lightness.map(|v| format!("{:.2}", v)).unwrap_or_default(),
+            entry.hue.map(|v| format!("{:.1}", v)).unwrap_or_default(),
+            entry.crop.map(|c| format!("{:.4},{:.4},{:.4},{:.4}", c.x, c.y, c.w, c.h)).unwrap_or_default(),
+        ));
+    }
+    if let Err(e) = fs::write(wall_meta_path(), content) {
+        eprintln!("Failed to save wallpaper metadata: {}", e);
+    }
+}
+
+/// Hue (degrees) of the most common saturated colour, None for greyscale-ish images.
+fn pixbuf_dominant_hue(pixbuf: &gtk4::gdk_pixbuf::Pixbuf) -> Option<f64> {
+    const BINS: usize = 24;
+    let width = pixbuf.width().max(0) as usize;
+    let height = pixbuf.height().max(0) as usize;
+    let rowstride = pixbuf.rowstride().max(0) as usize;
+    let n_channels = pixbuf.n_channels().max(0) as usize;
+    if n_channels < 3 {
+        return None;
+    }
+
+    let bytes = pixbuf.read_pixel_bytes();
+    let pixels: &[u8] = &bytes;
+    let mut bins = [0.0f64; BINS];
+    let mut samples = 0usize;
+
+    for y in 0..height {
+        for x in 0..width {
+            let offset = y * rowstride + x * n_channels;
+            let Some(px) = pixels.get(offset..offset + 3) else { continue };
+            samples += 1;
+
+            let (r, g, b) = (px[0] as f64 / 255.0, px[1] as f64 / 255.0, px[2] as f64 / 255.0);
+            let max = r.max(g).max(b);
+            let min = r.min(g).min(b);
+            let delta = max - min;
+            let saturation = if max > 0.0 { delta / max } else { 0.0 };
+            if saturation < 0.2 || max < 0.15 {
+                continue;
+            }
+
+            let hue = if max == r {
+                60.0 * ((g - b) / delta).rem_euclid(6.0)
+            } else if max == g {
+                60.0 * ((b - r) / delta + 2.0)
+            } else {
+                60.0 * ((r - g) / delta + 4.0)
+            };
+            bins[((hue / 360.0 * BINS as f64) as usize).min(BINS - 1)] += saturation;
+        }
+    }
+
+    let (best, weight) = bins
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.total_cmp(b.1))?;
+    if samples == 0 || *weight < samples as f64 * 0.05 {
+        return None;
+    }
+    Some((best as f64 + 0.5) * 360.0 / BINS as f64)
+}
+
+/// Videos without a sampled frame yet are skipped, the next refresh picks them up.
+/// Lightness and dominant hue of a wallpaper. Decodes the image, so only for worker threads.
+fn compute_wall_stats(path: &Path) -> (Option<f64>, Option<f64>) {
+    let Some(sample) = cached_wallpaper_sample(path) else { return (None, None) };
+    match gtk4::gdk_pixbuf::Pixbuf::from_file_at_scale(sample, 128, 128, true) {
+        Ok(pixbuf) => (pixbuf_lightness(&pixbuf, &[]), pixbuf_dominant_hue(&pixbuf)),
+        Err(_) => (None, None),
+    }
+}
+
+/// Works out the stats of wallpapers that have none yet off the main thread, then sorts the
+/// grid again. Every wallpaper is tried once per session, so an undecodable one doesn't loop.
+fn compute_missing_wall_stats(notiv_boxxy: &GtkBox, wall_state: &WallState, walls: &[(PathBuf, String, std::time::SystemTime)]) {
+    let missing: Vec<(PathBuf, String)> = {
+        let meta = wall_state.meta.borrow();
+        let mut tried = wall_state.stats_tried.borrow_mut();
+        walls
+            .iter()
+            .filter(|(_, filename, _)| meta.get(filename).is_none_or(|e| e.lightness.is_none()))
+            .filter(|(_, filename, _)| tried.insert(filename.clone()))
+            .map(|(path, filename, _)| (path.clone(), filename.clone()))
+            .collect()
+    };
+    if missing.is_empty() {
+        return;
+    }
+    let (notiv_boxxy, wall_state) = (notiv_boxxy.clone(), wall_state.clone());
+    glib::MainContext::default().spawn_local(async move {
+        let stats: Vec<(String, Option<f64>, Option<f64>)> = gtk4::gio::spawn_blocking(move || {
+            missing
+                .into_iter()
+                .map(|(path, filename)| {
+                    let (lightness, hue) = compute_wall_stats(&path);
+                    (filename, lightness, hue)
+                })
+                .collect()
+        })
+        .await
+        .unwrap_or_default();
+        if stats.iter().all(|(_, lightness, _)| lightness.is_none()) {
+            return;
+        }
+        {
+            let mut meta = wall_state.meta.borrow_mut();
+            for (filename, lightness, hue) in stats {
+                let entry = meta.entry(filename).or_default();
+                entry.lightness = lightness;
+                entry.hue = hue;
+            }
+            save_wall_meta(&meta);
+        }
+        if wall_state.sort.selected() >= 2 {
+            add_walls_to_grid(&notiv_boxxy, &wall_state);
+        }
+    });
+}
+
+// Load images dynamically
+fn add_walls_to_grid(notiv_boxxy: &GtkBox, wall_state: &WallState) {
+    let boxxy: gtk4::Widget = if wall_state.gallery_toggle.is_active() {
+        wall_state.gallery.clone().upcast()
+    } else {
+        wall_state.strip.clone().upcast()
+    };
+    while let Some(child) = wall_state.strip.first_child() {
+        wall_state.strip.remove(&child);
+    }
+    while let Some(child) = wall_state.gallery.first_child() {
+        wall_state.gallery.remove(&child);
+    }
+    wall_state.selected.borrow_mut().clear();
+
+    let home_dir = std::env::var("HOME").unwrap();
+    let wallpaper_dir = PathBuf::from(format!("{}/.config/swww/cynage", home_dir));
+
+    // the current wallpaper may have been removed or trashed
+    let current_gone = wall_state
+        .current_name
+        .borrow()
+        .as_ref()
+        .is_some_and(|name| !wallpaper_dir.join(name).exists() && current_wallpaper_path(&home_dir).is_none());
+    if current_gone {
+        show_current_wallpaper(wall_state, None);
+    }
+    let Ok(entries) = fs::read_dir(wallpaper_dir.clone()) else {
+        return;
+    };
+
+    let mut walls: Vec<(PathBuf, String, std::time::SystemTime)> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .filter_map(|path| {
+            let filename = path.file_name()?.to_string_lossy().to_string();
+            if filename.starts_with('.') {
+                return None;
+            }
+            let modified = fs::metadata(&path).and_then(|m| m.modified()).unwrap_or(std::time::UNIX_EPOCH);
+            Some((path, filename, modified))
+        })
+        .collect();
+
+    // 0 = name, 1 = date, 2 = brightness, 3 = dominant colour
+    let sort = wall_state.sort.selected();
+    if sort >= 2 {
+        // sorted with what is known now, the rest moves into place once computed
+        compute_missing_wall_stats(notiv_boxxy, wall_state, &walls);
+    }
+    {
+        let meta = wall_state.meta.borrow();
+        let lightness = |name: &String| meta.get(name).and_then(|e| e.lightness).unwrap_or(f64::MAX);
+        let hue = |name: &String| meta.get(name).and_then(|e| e.hue).unwrap_or(f64::MAX);
+        match sort {
+            1 => walls.sort_by_key(|(_, _, modified)| std::cmp::Reverse(*modified)),
+            2 => walls.sort_by(|a, b| lightness(&a.1).total_cmp(&lightness(&b.1))),
+            3 => walls.sort_by(|a, b| hue(&a.1).total_cmp(&hue(&b.1))),
+            _ => walls.sort_by_key(|(_, filename, _)| filename.to_lowercase()),
+        }
+    }
+
+    let notiv_clone_outer_for_wall = notiv_boxxy.clone();
+    wall_state.wall_buttons.borrow_mut().clear();
+    for (path, filename, _) in walls {
+        let img_path = path.clone();
+        let btn = Button::builder().build();
+        btn.set_css_classes(&["walls"]);
+        let thumb = gtk4::Overlay::new();
+        if let Some(image) = wall_thumbnail(&img_path, 260) {
+            image.set_pixel_size(260);
+            image.add_css_class("thumbnail");
+            thumb.set_child(Some(&image));
+        }
+        if is_video_wallpaper(&img_path) {
+            let badge = Label::new(Some("▶ video"));
+            badge.set_halign(gtk4::Align::Start);
+            badge.set_valign(gtk4::Align::End);
+            badge.add_css_class("wall_fav");
+            thumb.add_overlay(&badge);
+        }
+        let meta_entry = wall_state.meta.borrow().get(&filename).cloned().unwrap_or_default();
+        if meta_entry.favourite {
+            let star = Label::new(Some("★"));
+            star.set_halign(gtk4::Align::End);
+            star.set_valign(gtk4::Align::Start);
+            star.add_css_class("wall_fav");
+            thumb.add_overlay(&star);
+        }
+        if meta_entry.tags.is_empty() {
+            btn.set_tooltip_text(Some(&filename));
+        } else {
+            btn.set_tooltip_text(Some(&format!("{}\n{}", filename, meta_entry.tags.join(", "))));
+        }
+        btn.set_child(Some(&thumb));
+
+        // Clicking image button to execute script
+        let home_dir_cloned = home_dir.clone();
+        let filename_clone = filename.clone();
+        let wall_state_clone = wall_state.clone();
+        let notiv_clone_for_wall = notiv_clone_outer_for_wall.clone();
+        btn.connect_clicked(move |btn| {
+            if wall_state_clone.select_toggle.is_active() {
+                let mut selected = wall_state_clone.selected.borrow_mut();
+                let target = PathBuf::from(format!("{}/.config/swww/cynage/{}", home_dir_cloned, filename_clone));
+                if let Some(pos) = selected.iter().position(|p| *p == target) {
+                    selected.remove(pos);
+                    btn.remove_css_class("wall_selected");
+                } else {
+                    selected.push(target);
+                    btn.add_css_class("wall_selected");
+                }
+                return;
+            }
+
+            set_desktop_wallpaper(&filename_clone, &notiv_clone_for_wall, &wall_state_clone);
+        });
+        attach_wall_context_menu(&btn, &path, notiv_boxxy, wall_state);
+        if let Some(gallery) = boxxy.downcast_ref::<gtk4::FlowBox>() {
+            gallery.append(&btn);
+        } else if let Some(strip) = boxxy.downcast_ref::<GtkBox>() {
+            strip.append(&btn);
+        }
+        wall_state.wall_buttons.borrow_mut().push((filename, btn));
+    }
+    filter_walls(wall_state);
+}
+
+/// Search and the favourites toggle only hide thumbnails, the grid isn't rebuilt.
+/// Every search term has to match the file name or a tag.
+fn filter_walls(wall_state: &WallState) {
+    let query = wall_state.search.text().to_lowercase();
+    let terms: Vec<&str> = query.split_whitespace().collect();
+    let favourites_only = wall_state.favourites_toggle.is_active();
+    let meta = wall_state.meta.borrow();
+    for (filename, btn) in wall_state.wall_buttons.borrow().iter() {
+        let entry = meta.get(filename);
+        let visible = (!favourites_only || entry.is_some_and(|e| e.favourite))
+            && terms.iter().all(|term| {
+                filename.to_lowercase().contains(term)
+                    || entry.is_some_and(|e| e.tags.iter().any(|t| t.to_lowercase().contains(term)))
+            });
+        // in the gallery the FlowBoxChild would keep an empty slot
+        match btn.parent().and_downcast::<gtk4::FlowBoxChild>() {
+            Some(child) => child.set_visible(visible),
+            None => btn.set_visible(visible),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum WallRemoval {
+    Remove,
+    Trash,
+}
+
+/// Wallpaper swww last set according to wall.ctl, None on a fresh account or when the file is gone.
+/// A cropped copy counts as the wallpaper it was cut from.
+fn current_wallpaper_path(home_dir: &str) -> Option<PathBuf> {
+    let content = fs::read_to_string(format!("{}/.config/swww/wall.ctl", home_dir)).ok()?;
+    let path = content.lines().next()?.split('|').next_back()?.trim();
+    if path.is_empty() {
+        return None;
+    }
+    let abs_path = match path.strip_prefix("~/") {
+        Some(rest) => PathBuf::from(format!("{}/{}", home_dir, rest)),
+        None => PathBuf::from(path),
+    };
+    let original = original_wallpaper(&abs_path, home_dir);
+    original.is_file().then_some(original)
+}
+
+/// What swww shows for a wallpaper: the cropped copy if it has a crop, otherwise the file itself.
+fn displayed_wallpaper(wall_state: &WallState, path: &Path) -> PathBuf {
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let has_crop = wall_state.meta.borrow().get(&name).is_some_and(|e| e.crop.is_some());
+    cropped_wallpaper_path(path)
+        .filter(|cropped| has_crop && cropped.is_file())
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+fn show_current_wallpaper(wall_state: &WallState, path: Option<&Path>) {
+    let texture = path
+        .and_then(wallpaper_sample)
+        .and_then(|sample| gtk4::gdk::Texture::from_file(&gtk4::gio::File::for_path(sample)).ok());
+    match texture {
+        Some(texture) => {
+            wall_state.current_pic.borrow().set_paintable(Some(&texture));
+            wall_state.current_preview.set_visible_child_name("picture");
+        }
+        None => {
+            wall_state.current_pic.borrow().set_paintable(None::<&gtk4::gdk::Paintable>);
+            wall_state.current_preview.set_visible_child_name("placeholder");
+            *wall_state.current_name.borrow_mut() = None;
+        }
+    }
+}
+
+fn set_desktop_wallpaper(filename: &str, notiv_boxxy: &GtkBox, wall_state: &WallState) {
+    let home_dir = env::var("HOME").unwrap_or_default();
+    let target_path = format!("{}/.config/swww/cynage/{}", home_dir, filename);
+    let output = wall_state
+        .video_output
+        .selected_item()
+        .and_downcast::<gtk4::StringObject>()
+        .map(|o| o.string().to_string())
+        .filter(|name| name != "All monitors")
+        .unwrap_or_else(|| "ALL".to_string());
+    let shown_path = if is_video_wallpaper(Path::new(&target_path)) {
+        if let Err(e) = set_video_wallpaper(Path::new(&target_path), &output) {
+            show_notification(notiv_boxxy, &format!("could not start mpvpaper: {}", e));
+            return;
+        }
+        let media = gtk4::MediaFile::for_filename(&target_path);
+        media.set_muted(true);
+        media.set_loop(true);
+        media.play();
+        wall_state.current_pic.borrow_mut().set_paintable(Some(&media));
+        wall_state.current_preview.set_visible_child_name("picture");
+        PathBuf::from(&target_path)
+    } else {
+        stop_video_wallpapers(&output);
+        // a stored crop is applied by handing swww a cropped copy
+        let crop = wall_state.meta.borrow().get(filename).and_then(|e| e.crop);
+        let set_path = crop
+            .and_then(|c| cropped_wallpaper(Path::new(&target_path), &c))
+            .unwrap_or_else(|| PathBuf::from(&target_path));
+        let script_path = format!("{}/.config/hypr/scripts/swwwallpaper.sh", home_dir);
+        let _ = Command::new(script_path).arg("-s").arg(&set_path).spawn();
+        show_current_wallpaper(wall_state, Some(&set_path));
+        set_path
+    };
+    *wall_state.current_name.borrow_mut() = Some(filename.to_string());
+    let ignored = wall_state.auto_theme.borrow().is_ignored(filename);
+    wall_state.ignore_check.set_active(ignored);
+
+    let config = wall_state.auto_theme.borrow();
+
+    let prefer_light = is_system_theme_light();
+    match is_image_dark(filename, &shown_path, &config) {
+        Some(true) if prefer_light => {
+            show_notification(notiv_boxxy, "wallpaper changed, dark wallpaper detected");
+            let _ = Command::new("cynagectl").arg("-s").arg("dark").spawn();
+        }
+        Some(false) if !prefer_light => {
+            show_notification(notiv_boxxy, "wallpaper changed, Light wallpaper detected");
+            let _ = Command::new("cynagectl").arg("-s").arg("light").spawn();
+        }
+        _ => show_notification(notiv_boxxy, "wallpaper changed"),
+    }
+}
+
+fn attach_wall_context_menu(btn: &Button, path: &Path, notiv_boxxy: &GtkBox, wall_state: &WallState) {
+    let popover = gtk4::Popover::new();
+    let menu = GtkBox::new(Orientation::Vertical, 2);
+    let remove_btn = Button::with_label("Remove");
+    let trash_btn = Button::with_label("Move to Trash");
+    let reveal_btn = Button::with_label("Reveal in file manager");
+    let filename = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+    let meta_entry = wall_state.meta.borrow().get(&filename).cloned().unwrap_or_default();
+    let fav_btn = Button::with_label(if meta_entry.favourite { "Unfavourite" } else { "Favourite" });
+    let tags_entry = gtk4::Entry::new();
+    tags_entry.set_placeholder_text(Some("tags, comma separated"));
+    tags_entry.set_text(&meta_entry.tags.join(", "));
+    // '|' separates the fields of wallmeta.ctl
+    tags_entry.connect_insert_text(|entry, text, _| {
+        if text.contains('|') {
+            entry.stop_signal_emission_by_name("insert-text");
+            entry.error_bell();
+        }
+    });
+    let crop_btn = Button::with_label("Crop / position…");
+    let lock_btn = Button::with_label("Set as lock screen");
+    let both_btn = Button::with_label("Set as desktop and lock screen");
+    menu.append(&fav_btn);
+    menu.append(&tags_entry);
+    menu.append(&crop_btn);
+    menu.append(&lock_btn);
+    menu.append(&both_btn);
+    menu.append(&remove_btn);
+    menu.append(&trash_btn);
+    menu.append(&reveal_btn);
+    popover.set_child(Some(&menu));
+    popover.set_parent(btn);
+    let popover_clone = popover.clone();
+    btn.connect_destroy(move |_| popover_clone.unparent());
+
+    // right-clicking a selected thumbnail acts on the whole selection
+    let targets = {
+        let path = path.to_path_buf();
+        let selected = wall_state.selected.clone();
+        move || {
+            let selected = selected.borrow();
+            if selected.contains(&path) { selected.clone() } else { vec![path.clone()] }
+        }
+    };
+
+    for (button, removal) in [(&remove_btn, WallRemoval::Remove), (&trash_btn, WallRemoval::Trash)] {
+        let popover = popover.clone();
+        let targets = targets.clone();
+        let notiv_boxxy = notiv_boxxy.clone();
+        let wall_state = wall_state.clone();
+        button.connect_clicked(move |_| {
+            popover.popdown();
+            confirm_wallpaper_removal(targets(), removal, &notiv_boxxy, &wall_state);
+        });
+    }
+
+    let popover_clone = popover.clone();
+    let notiv_clone = notiv_boxxy.clone();
+    let wall_state_clone = wall_state.clone();
+    let filename_clone = filename.clone();
+    fav_btn.connect_clicked(move |_| {
+        popover_clone.popdown();
+        {
+            let mut meta = wall_state_clone.meta.borrow_mut();
+            let entry = meta.entry(filename_clone.clone()).or_default();
+            entry.favourite = !entry.favourite;
+            save_wall_meta(&meta);
+        }
+        // the popover lives on the thumbnail, rebuild once it has closed
+        let notiv_clone = notiv_clone.clone();
+        let wall_state_clone = wall_state_clone.clone();
+        glib::idle_add_local_once(move || add_walls_to_grid(&notiv_clone, &wall_state_clone));
+    });
+
+    let popover_clone = popover.clone();
+    let notiv_clone = notiv_boxxy.clone();
+    let wall_state_clone = wall_state.clone();
+    tags_entry.connect_activate(move |entry| {
+        popover_clone.popdown();
+        {
+            let mut meta = wall_state_clone.meta.borrow_mut();
+            meta.entry(filename.clone()).or_default().tags = parse_wall_tags(&entry.text());
+            save_wall_meta(&meta);
+        }
+        let notiv_clone = notiv_clone.clone();
+        let wall_state_clone = wall_state_clone.clone();
+        glib::idle_add_local_once(move || add_walls_to_grid(&notiv_clone, &wall_state_clone));
+    });
+
+    let popover_clone = popover.clone();
+    let notiv_clone = notiv_boxxy.clone();
+    let wall_state_clone = wall_state.clone();
+    let crop_path = path.to_path_buf();
+    crop_btn.connect_clicked(move |_| {
+        popover_clone.popdown();
+        open_crop_editor(&crop_path, &notiv_clone, &wall_state_clone);
+    });
+
+    for (button, also_desktop) in [(&lock_btn, false), (&both_btn, true)] {
+        let popover_clone = popover.clone();
+        let notiv_clone = notiv_boxxy.clone();
+        let wall_state_clone = wall_state.clone();
+        let filename = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        button.connect_clicked(move |_| {
+            popover_clone.popdown();
+            open_lock_screen_dialog(&filename, also_desktop, &notiv_clone, &wall_state_clone);
+        });
+    }
+
+    let popover_clone = popover.clone();
+    reveal_btn.connect_clicked(move |_| {
+        popover_clone.popdown();
+        reveal_in_file_manager(&targets());
+    });
+
+    let right_click = gtk4::GestureClick::new();
+    right_click.set_button(gdk::BUTTON_SECONDARY);
+    right_click.connect_pressed(move |_, _, _, _| {
+        popover.popup();
+    });
+    btn.add_controller(right_click);
+}
+
+fn confirm_wallpaper_removal(paths: Vec<PathBuf>, removal: WallRemoval, notiv_boxxy: &GtkBox, wall_state: &WallState) {
+    if paths.is_empty() {
+        show_notification(notiv_boxxy, "no wallpapers selected");
+        return;
+    }
+
+    let names: Vec<String> = paths
+        .iter()
+        .filter_map(|p| p.file_name())
+        .map(|n| n.to_string_lossy().to_string())
+        .collect();
+    let (question, verb) = match removal {
+        WallRemoval::Remove => (format!("Remove {} wallpaper(s)?", paths.len()), "Remove"),
+        WallRemoval::Trash => (format!("Move {} wallpaper(s) to the trash?", paths.len()), "Move to Trash"),
+    };
+
+    let dialog = MessageDialog::builder()
+        .text(question)
+        .secondary_text(names.join("\n"))
+        .modal(true)
+        .build();
+    if let Some(window) = wall_state.scroller.root().and_downcast::<gtk4::Window>() {
+        dialog.set_transient_for(Some(&window));
+    }
+    dialog.add_buttons(&[("Cancel", ResponseType::Cancel), (verb, ResponseType::Accept)]);
+
+    let notiv_boxxy = notiv_boxxy.clone();
+    let wall_state = wall_state.clone();
+    dialog.connect_response(move |dialog, response| {
+        dialog.close();
+        if response != ResponseType::Accept {
+            return;
+        }
+        match removal {
+            WallRemoval::Remove => remove_wallpapers(paths.clone(), &notiv_boxxy, &wall_state),
+            WallRemoval::Trash => trash_wallpapers(paths.clone(), &notiv_boxxy, &wall_state),
+        }
+    });
+    dialog.show();
+}
+
+fn move_file(from: &Path, to: &Path) -> std::io::Result<()> {
+    fs::rename(from, to).or_else(|_| {
+        fs::copy(from, to)?;
+        fs::remove_file(from)
+    })
 }
 
-fn getty_wifi_status() -> bool {
-    let conn = dbus::blocking::Connection::new_system().unwrap();
-    let nm = NetworkManager::new(&conn);
-    nm.wireless_enabled().unwrap_or(false)
+fn removed_wallpapers_dir() -> PathBuf {
+    let home_dir = env::var("HOME").unwrap_or_default();
+    PathBuf::from(format!("{}/.cache/calibrate/removed", home_dir))
 }
 
-fn setty_wifi_enabled(enabled: bool) {
-    let state = if enabled { "on" } else { "off" };
-    let _ = Command::new("nmcli").args(&["radio", "wifi", state]).status();
+/// Deletes the parked file and drops it from cynagectl's index by its full file name, since a
+/// stem can't tell `sunset.jpg` from `sunset.png`.
+fn commit_wallpaper_removal(original: &Path, parked: &Path) {
+    if let Err(e) = fs::remove_file(parked) {
+        eprintln!("Failed to remove {}: {}", parked.display(), e);
+        return;
+    }
+    // re-imported during the undo window: the index entry belongs to the new file now
+    if original.exists() {
+        return;
+    }
+    let name = original.file_name().unwrap_or_default();
+    let forgotten = Command::new("cynagectl")
+        .args(["-w", "remove"])
+        .arg(name)
+        .status()
+        .is_ok_and(|status| status.success());
+    if !forgotten {
+        eprintln!("cynagectl could not forget {}", original.display());
+    }
 }
 
-fn clean_ssid(s: &str) -> String {
-    // Remove leading/trailing whitespace and quotes
-    let s = s.trim();
-    s.trim_matches('"').to_string()
+/// Moves a parked wallpaper back, next to a same-named file imported in the meantime rather
+/// than over it.
+fn restore_parked_wallpaper(original: &Path, parked: &Path) -> std::io::Result<PathBuf> {
+    let dest = if original.exists() {
+        let dir = original.parent().unwrap_or(Path::new("."));
+        let stem = original.file_stem().unwrap_or_default().to_string_lossy();
+        let ext = original.extension().unwrap_or_default().to_string_lossy();
+        unique_destination(dir, &stem, &ext)
+    } else {
+        original.to_path_buf()
+    };
+    move_file(parked, &dest)?;
+    Ok(dest)
 }
 
-fn get_active_ssid() -> Option<String> {
-    // Use nmcli to get the current active SSID, clean, and return it
-    if let Ok(out) = Command::new("nmcli")
-        .args(&["-t", "-f", "active,ssid", "dev", "wifi"])
-        .output()
-    {
-        if let Ok(text) = String::from_utf8(out.stdout) {
-            for line in text.lines() {
-                if line.starts_with("yes:") {
-                    let ssid = &line[4..];
-                    let cleaned = clean_ssid(ssid);
-                    if !cleaned.is_empty() && cleaned != "--" {
-                        return Some(cleaned);
-                    }
-                }
+/// Commits removals whose undo toast never expired (the app was closed first).
+fn flush_removed_wallpapers() {
+    let home_dir = env::var("HOME").unwrap_or_default();
+    let wallpaper_dir = PathBuf::from(format!("{}/.config/swww/cynage", home_dir));
+    let Ok(entries) = fs::read_dir(removed_wallpapers_dir()) else {
+        return;
+    };
+    for parked in entries.flatten().map(|e| e.path()) {
+        let name = parked.file_name().unwrap_or_default().to_string_lossy().to_string();
+        match name.split_once("__") {
+            Some((_, original)) => commit_wallpaper_removal(&wallpaper_dir.join(original), &parked),
+            None => {
+                let _ = fs::remove_file(&parked);
             }
         }
     }
-    None
 }
 
-fn refresh_wifi_listty(conn: &DbusConnection, network_list: &GtkBox) {
-    // Remove all children
-    while let Some(child) = network_list.first_child() {
-        network_list.remove(&child);
+/// Removed wallpapers are parked in ~/.cache/calibrate/removed as "<random>__<name>" until the
+/// undo toast expires; whatever is left there is committed on the next start or at shutdown.
+fn remove_wallpapers(paths: Vec<PathBuf>, notiv_boxxy: &GtkBox, wall_state: &WallState) {
+    let staging = removed_wallpapers_dir();
+    if let Err(e) = fs::create_dir_all(&staging) {
+        show_notification(notiv_boxxy, &format!("could not remove wallpapers: {}", e));
+        return;
     }
 
-    // Collect the current connected SSID
-    let active_ssid = get_active_ssid();
-
-    let nm = NetworkManager::new(conn);
-    let devices = match nm.get_devices() {
-        Ok(devs) => devs,
-        Err(_) => return,
-    };
-
-    let button_vec = Rc::new(RefCell::new(Vec::new()));
-
-    for dev in devices {
-        if let Device::WiFi(wifi_dev) = dev {
-            let aps = match wifi_dev.get_access_points() { Ok(aps) => aps, Err(_) => continue };
-            for ap in aps {
-                let ssid = ap.ssid().unwrap_or_else(|_| "(unknown SSID)".to_string());
-                let clean_button_ssid = clean_ssid(&ssid);
-                let strength = ap.strength().unwrap_or(0);
-
-                let button = Button::with_label(&format!("{} ({}%)", ssid, strength));
-                button.set_css_classes(&["network_label"]);
+    let mut staged: Vec<(PathBuf, PathBuf)> = Vec::new();
+    for path in paths {
+        let Some(name) = path.file_name() else { continue };
+        // two wallpapers with the same name (removed in separate batches) must not collide
+        let parked = staging.join(format!("{}__{}", glib::uuid_string_random(), name.to_string_lossy()));
+        match move_file(&path, &parked) {
+            Ok(()) => staged.push((path, parked)),
+            Err(e) => eprintln!("Failed to remove {}: {}", path.display(), e),
+        }
+    }
+    add_walls_to_grid(notiv_boxxy, wall_state);
+    if staged.is_empty() {
+        show_notification(notiv_boxxy, "could not remove wallpapers");
+        return;
+    }
 
-                // Highlight the already-connected SSID
-                if let Some(ref active) = active_ssid {
-                    if &clean_button_ssid == active {
-                        button.add_css_class("connected");
-                    }
+    let staged = Rc::new(staged);
+    let staged_undo = staged.clone();
+    let notiv_clone = notiv_boxxy.clone();
+    let wall_state_commit = wall_state.clone();
+    let wall_state = wall_state.clone();
+    show_undo_notification(
+        notiv_boxxy,
+        &format!("removed {} wallpaper(s)", staged.len()),
+        move || {
+            for (original, parked) in staged_undo.iter() {
+                if let Err(e) = restore_parked_wallpaper(original, parked) {
+                    eprintln!("Failed to restore {}: {}", original.display(), e);
+                }
+            }
+            add_walls_to_grid(&notiv_clone, &wall_state);
+            show_notification(&notiv_clone, "wallpapers restored");
+        },
+        move || {
+            let originals: Vec<PathBuf> = staged.iter().map(|(original, _)| original.clone()).collect();
+            forget_wall_meta(&wall_state_commit, &originals);
+            let staged: Vec<(PathBuf, PathBuf)> = staged.iter().cloned().collect();
+            gtk4::gio::spawn_blocking(move || {
+                for (original, parked) in &staged {
+                    commit_wallpaper_removal(original, parked);
                 }
+            });
+        },
+    );
+}
 
-                button_vec.borrow_mut().push(button.clone());
-                let ssid_clone = ssid.clone();
-                let button_vec_clone = button_vec.clone();
-                let button_clone = button.clone();
-
-                button.connect_clicked(move |_| {
-                    let output = Command::new("nmcli")
-                        .args(&["device", "wifi", "connect", &ssid_clone])
-                        .output();
-
-                    let mut connected = false;
-                    if let Ok(_out) = output {
-                        let check = Command::new("nmcli")
-                            .args(&["-t", "-f", "active,ssid", "dev", "wifi"])
-                            .output();
-
-                        if let Ok(check) = check {
-                            if let Ok(outstr) = String::from_utf8(check.stdout) {
-                                for line in outstr.lines() {
-                                    if line.starts_with("yes:") && clean_ssid(&line[4..]) == clean_ssid(&ssid_clone) {
-                                        connected = true;
-                                        break;
-                                    }
-                                }
-                            }
-                        }
-                    }
+fn trash_wallpapers(paths: Vec<PathBuf>, notiv_boxxy: &GtkBox, wall_state: &WallState) {
+    let mut trashed = Vec::new();
+    for path in paths {
+        match gtk4::gio::File::for_path(&path).trash(None::<&gtk4::gio::Cancellable>) {
+            Ok(()) => trashed.push(path),
+            Err(e) => eprintln!("Failed to trash {}: {}", path.display(), e),
+        }
+    }
+    add_walls_to_grid(notiv_boxxy, wall_state);
+    if trashed.is_empty() {
+        show_notification(notiv_boxxy, "could not move wallpapers to the trash");
+        return;
+    }
 
-                    // Remove 'connected' from all, add only to this one if successful
-                    for btn in button_vec_clone.borrow().iter() {
-                        btn.remove_css_class("connected");
-                    }
+    let count = trashed.len();
+    let notiv_clone = notiv_boxxy.clone();
+    let trashed = Rc::new(trashed);
+    let (trashed_commit, wall_state_commit) = (trashed.clone(), wall_state.clone());
+    let wall_state = wall_state.clone();
+    show_undo_notification(
+        notiv_boxxy,
+        &format!("moved {} wallpaper(s) to the trash", count),
+        move || {
+            let restored = restore_from_trash(&trashed);
+            add_walls_to_grid(&notiv_clone, &wall_state);
+            show_notification(&notiv_clone, &format!("restored {} of {} wallpapers", restored, trashed.len()));
+        },
+        move || forget_wall_meta(&wall_state_commit, &trashed_commit),
+    );
+}
 
-                    if connected {
-                        eprint!("connect");
-                        button_clone.add_css_class("connected");
-                    } else {
-                        eprint!("failed");
-                        button_clone.remove_css_class("connected");
-                    }
-                });
+fn restore_from_trash(paths: &[PathBuf]) -> usize {
+    use gtk4::gio::{Cancellable, File, FileCopyFlags, FileQueryInfoFlags};
+
+    let trash = File::for_uri("trash:///");
+    let Ok(entries) = trash.enumerate_children("standard::name,trash::orig-path", FileQueryInfoFlags::NONE, None::<&Cancellable>) else {
+        return 0;
+    };
 
-                network_list.append(&button);
+    let mut remaining: Vec<&PathBuf> = paths.iter().collect();
+    let mut restored = 0;
+    for info in entries.flatten() {
+        let Some(orig) = info.attribute_byte_string("trash::orig-path") else { continue };
+        let orig = PathBuf::from(orig.as_str());
+        let Some(pos) = remaining.iter().position(|p| **p == orig) else { continue };
+
+        let item = trash.child(info.name());
+        match item.move_(&File::for_path(&orig), FileCopyFlags::NONE, None::<&Cancellable>, None) {
+            Ok(()) => {
+                remaining.remove(pos);
+                restored += 1;
             }
+            Err(e) => eprintln!("Failed to restore {}: {}", orig.display(), e),
         }
     }
+    restored
 }
 
+fn reveal_in_file_manager(paths: &[PathBuf]) {
+    let uris: Vec<String> = paths.iter().map(|p| gtk4::gio::File::for_path(p).uri().to_string()).collect();
 
+    let shown = DbusConnection::new_session().ok().is_some_and(|conn| {
+        let proxy = conn.with_proxy("org.freedesktop.FileManager1", "/org/freedesktop/FileManager1", std::time::Duration::from_secs(2));
+        proxy
+            .method_call::<(), _, _, _>("org.freedesktop.FileManager1", "ShowItems", (uris, ""))
+            .is_ok()
+    });
+
+    if !shown {
+        if let Some(parent) = paths.first().and_then(|p| p.parent()) {
+            let _ = Command::new("xdg-open").arg(parent).spawn();
+        }
+    }
+}
 
 fn load_css() {
     let csss = r#"
@@ -568,6 +7026,13 @@ fn load_css() {
         }
 
 
+        listview.network_list,
+        listview.network_list > row {
+            background-color: rgba(0, 0, 0, 0);
+            padding: 0px;
+            margin-bottom: 10px;
+        }
+
         .network_label {
             color: rgb(2, 71, 59);
             letter-spacing: 2px;
@@ -575,6 +7040,24 @@ fn load_css() {
             font-weight: 700;
         }
 
+        box.network_row {
+            padding: 0px;
+        }
+
+        label.network_badge {
+            font-size: 11px;
+            letter-spacing: 1px;
+            padding: 2px 6px;
+            border: 1px solid rgba(5, 148, 122, 0.63);
+        }
+
+        label.network_details {
+            font-family: monospace;
+            font-size: 12px;
+            padding: 10px 25px;
+            color: rgb(5, 148, 122);
+        }
+
         button {
             all: unset;
             background-color: rgba(255, 255, 255, 0);
@@ -606,6 +7089,15 @@ fn load_css() {
 
         }
 
+        label.wall_fav {
+            color: rgb(255, 230, 0);
+            padding: 4px;
+        }
+
+        button.wall_selected {
+            background-color: rgba(5, 148, 122, 0.63);
+        }
+
         button.connected {
             background-color: rgba(0, 0, 0, 0.2);
         }
@@ -642,12 +7134,6 @@ fn load_css() {
             border-radius: 10px;
         }
 
-        .termbox {
-            padding: 0px;
-            background-color: #00BC7F;
-            border: 5px solid rgb(5, 148, 122);
-        }
-
         .display_win scrollbar {
             background-color: transparent;
             border: none;
@@ -776,6 +7262,22 @@ fn load_css() {
             margin-top: 10px;
         }
 
+        label.autostart {
+            font-size: 8px;
+            letter-spacing: 0px;
+            line-height: 1.2;
+            padding: 0;
+            margin-top: 10px;
+        }
+
+        label.events {
+            font-size: 8px;
+            letter-spacing: 0px;
+            line-height: 0.9;
+            padding: 0;
+            margin-top: 10px;
+        }
+
         label.calibrate {
             font-size: 18px;
             padding: 5px;
@@ -1077,26 +7579,28 @@ fn build_ui(app: &Application) {
     let wallpaper_box = GtkBox::builder().orientation(Orientation::Vertical).spacing(0).build();
     let current_wall = GtkBox::new(Orientation::Horizontal, 2);
 
-    let config_path = format!("{}/.config/swww/wall.ctl", home_dir);
-    let content = fs::read_to_string(&config_path)
-        .unwrap_or_else(|_| panic!("Failed to read {}", config_path));
-    
-    let mut current_pic_ref_opt: Option<Rc<RefCell<gtk4::Picture>>> = None;
- 
-    if let Some(line) = content.lines().next() {
-        if let Some(path) = line.split('|').last() {
-            let abs_path = if path.starts_with("~/") {
-                format!("{}/{}", home_dir, &path[2..])
-            } else {
-                path.to_string()
-            };
-            let file = gtk4::gio::File::for_path(abs_path);
-            let texture = gtk4::gdk::Texture::from_file(&file).unwrap();
-            let current_pic = gtk4::Picture::for_paintable(&texture);
-            current_pic_ref_opt = Some(Rc::new(RefCell::new(current_pic.clone())));
-            current_wall.append(&current_pic);
-        }
-    }
+    let current_wall_path = current_wallpaper_path(&home_dir);
+    let current_wall_name: Rc<RefCell<Option<String>>> = Rc::new(RefCell::new(
+        current_wall_path
+            .as_ref()
+            .and_then(|p| p.file_name())
+            .map(|n| n.to_string_lossy().to_string()),
+    ));
+
+    // preview of the current wallpaper, or a first-run prompt when there is none
+    let current_preview = Stack::new();
+    let current_pic = gtk4::Picture::new();
+    let wall_placeholder = GtkBox::new(Orientation::Vertical, 10);
+    wall_placeholder.set_valign(gtk4::Align::Center);
+    wall_placeholder.set_halign(gtk4::Align::Center);
+    wall_placeholder.set_size_request(400, -1);
+    let placeholder_label = Label::new(Some("No wallpaper set"));
+    let pick_wall = Button::with_label("Pick a wallpaper");
+    wall_placeholder.append(&placeholder_label);
+    wall_placeholder.append(&pick_wall);
+    current_preview.add_named(&current_pic, Some("picture"));
+    current_preview.add_named(&wall_placeholder, Some("placeholder"));
+    current_wall.append(&current_preview);
         
     // let display_label = gtk4::Label::new(Some(&format!("Display: {}", display_name)));
     // display_label.set_justify(gtk4::Justification::Right);
@@ -1119,16 +7623,70 @@ fn build_ui(app: &Application) {
     wall_buttons.set_valign(gtk4::Align::Baseline);
     wall_buttons.set_halign(gtk4::Align::End);
     let add_wall = Button::builder().child(&Label::new(Some("Add wallpapers"))).build();
-    let remove_wall = Button::builder().child(&Label::new(Some("Remove wallpaper"))).build();
+    let select_wall = gtk4::ToggleButton::with_label("Select");
+    let remove_wall = Button::builder().child(&Label::new(Some("Remove selected"))).build();
+    let trash_wall = Button::builder().child(&Label::new(Some("Move to Trash"))).build();
+    remove_wall.set_visible(false);
+    trash_wall.set_visible(false);
     let vdummy_forwallinfo = GtkBox::new(Orientation::Vertical, 15);
     vdummy_forwallinfo.set_vexpand(true);
 
     wall_buttons.append(&add_wall);
+    wall_buttons.append(&select_wall);
     wall_buttons.append(&remove_wall);
+    wall_buttons.append(&trash_wall);
 
     // wall_info.append(&display_label);
     // wall_info.append(&resolution_label);
+    // auto theme
+    let auto_theme = Rc::new(RefCell::new(load_auto_theme_config()));
+    let auto_theme_box = GtkBox::new(Orientation::Horizontal, 5);
+    auto_theme_box.set_halign(gtk4::Align::End);
+    let threshold_label = Label::new(Some("Auto theme dark below lightness"));
+    let threshold_scale = gtk4::Scale::with_range(Orientation::Horizontal, 0.0, 100.0, 1.0);
+    threshold_scale.set_value(auto_theme.borrow().threshold);
+    threshold_scale.set_draw_value(true);
+    threshold_scale.set_size_request(200, -1);
+    let auto_theme_clone = auto_theme.clone();
+    threshold_scale.connect_value_changed(move |scale| {
+        let mut config = auto_theme_clone.borrow_mut();
+        config.threshold = scale.value();
+        save_auto_theme_config(&config);
+    });
+
+    let ignore_check = gtk4::CheckButton::with_label("Ignore auto-theme for this wallpaper");
+    if let Some(name) = current_wall_name.borrow().as_ref() {
+        ignore_check.set_active(auto_theme.borrow().is_ignored(name));
+    }
+    let auto_theme_clone = auto_theme.clone();
+    let current_wall_name_clone = current_wall_name.clone();
+    ignore_check.connect_toggled(move |check| {
+        if let Some(name) = current_wall_name_clone.borrow().as_ref() {
+            let mut config = auto_theme_clone.borrow_mut();
+            if config.is_ignored(name) != check.is_active() {
+                config.set_ignored(name, check.is_active());
+                save_auto_theme_config(&config);
+            }
+        }
+    });
+
+    auto_theme_box.append(&threshold_label);
+    auto_theme_box.append(&threshold_scale);
+
+    // which monitor a video wallpaper plays on
+    let video_output_box = GtkBox::new(Orientation::Horizontal, 5);
+    video_output_box.set_halign(gtk4::Align::End);
+    let mut video_outputs = vec!["All monitors".to_string()];
+    video_outputs.extend(monitor_names());
+    let video_output_refs: Vec<&str> = video_outputs.iter().map(|s| s.as_str()).collect();
+    let video_output = gtk4::DropDown::from_strings(&video_output_refs);
+    video_output_box.append(&Label::new(Some("Play video wallpapers on")));
+    video_output_box.append(&video_output);
+
     wall_info.append(&vdummy_forwallinfo);
+    wall_info.append(&auto_theme_box);
+    wall_info.append(&ignore_check);
+    wall_info.append(&video_output_box);
     wall_info.append(&wall_buttons);
 
     current_wall.append(&wall_info);
@@ -1149,77 +7707,70 @@ fn build_ui(app: &Application) {
         .spacing(10)
         .build();
 
-    scrolled_window.set_child(Some(&image_grid));
+    let image_gallery = gtk4::FlowBox::builder()
+        .selection_mode(gtk4::SelectionMode::None)
+        .homogeneous(true)
+        .column_spacing(10)
+        .row_spacing(10)
+        .valign(gtk4::Align::Start)
+        .build();
 
-    wallpaper_box.append(&current_wall);
-    wallpaper_box.append(&scrolled_window);
+    scrolled_window.set_child(Some(&image_grid));
 
-    // Load images dynamically
-    let add_walls_to_grid = |boxxy: &GtkBox, notiv_boxxy: &GtkBox, picc_ref: &Rc<RefCell<gtk4::Picture>>| {
-        while let Some(child) = boxxy.first_child() {
-            boxxy.remove(&child);
-        }
-        let home_dir = std::env::var("HOME").unwrap();
-        let wallpaper_dir = PathBuf::from(format!("{}/.config/swww/cynage", home_dir));
-        if let Ok(entries) = fs::read_dir(wallpaper_dir.clone()) {
-            let notiv_clone_outer_for_wall = notiv_boxxy.clone();
-            for entry in entries.flatten() {
-                let path = entry.path();
-                if path.is_file() {
-                    let filename = path.file_name().unwrap().to_string_lossy().to_string();
-                    let img_path = path.clone();
-                    let btn = Button::builder().build();
-                    btn.set_css_classes(&["walls"]);
-                    let pixbuf = gtk4::gdk_pixbuf::Pixbuf::from_file_at_scale(img_path, 260, 260, true).ok();
-                    if let Some(pix) = pixbuf {
-                        let image = Image::from_pixbuf(Some(&pix));
-                        image.set_pixel_size(260);
-                        image.add_css_class("thumbnail");
-                        btn.set_child(Some(&image));
-                    }
+    // search, sort and view switch
+    let wall_toolbar = GtkBox::new(Orientation::Horizontal, 5);
+    let wall_search = gtk4::SearchEntry::new();
+    wall_search.set_placeholder_text(Some("search name or tag"));
+    wall_search.set_hexpand(true);
+    let wall_sort = gtk4::DropDown::from_strings(&["Name", "Date", "Brightness", "Color"]);
+    let wall_favourites = gtk4::ToggleButton::with_label("Favourites");
+    let wall_gallery = gtk4::ToggleButton::with_label("Gallery");
+    wall_toolbar.append(&wall_search);
+    wall_toolbar.append(&Label::new(Some("Sort")));
+    wall_toolbar.append(&wall_sort);
+    wall_toolbar.append(&wall_favourites);
+    wall_toolbar.append(&wall_gallery);
+
+    // pictures from the wallpaper folder, or a generated color
+    let wall_sources = Stack::builder().transition_type(gtk4::StackTransitionType::None).build();
+    let wall_images = GtkBox::new(Orientation::Vertical, 0);
+    wall_images.append(&wall_toolbar);
+    wall_images.append(&scrolled_window);
+    wall_sources.add_titled(&wall_images, Some("images"), "Wallpapers");
+    let wall_sources_switcher = gtk4::StackSwitcher::builder().stack(&wall_sources).halign(gtk4::Align::Start).build();
 
-                    // Clicking image button to execute script
-                    let home_dir_cloned = home_dir.clone();
-                    let filename_clone = filename.clone();
-                    let current_pic_clone = picc_ref.clone();
-                    let notiv_clone_for_wall = notiv_clone_outer_for_wall.clone(); 
-                    btn.connect_clicked(move |_| {
-                        let target_path = format!("{}/.config/swww/cynage/{}", home_dir_cloned, filename_clone);
-                        let script_path = format!("{}/.config/hypr/scripts/swwwallpaper.sh", home_dir_cloned);
-                        let _ = Command::new(script_path).arg("-s").arg(&target_path).spawn();
-                        let file = gtk4::gio::File::for_path(&target_path);
-                        if let Ok(texture) = gtk4::gdk::Texture::from_file(&file) {
-                            let new_pic = gtk4::Picture::for_paintable(&texture);
-                            new_pic.set_hexpand(true);
-                            new_pic.set_vexpand(true);
-                            new_pic.set_halign(gtk4::Align::Fill);
-                            new_pic.set_valign(gtk4::Align::Fill);
-                            current_pic_clone.borrow_mut().set_paintable(Some(&texture));
-                        }
-                        let now = is_image_dark(&target_path);
-                        let prefer_output = is_system_theme_light();
-                        if now && prefer_output {
-                            show_notification(&notiv_clone_for_wall, "wallpaper changed, dark wallpaper detected");
-                            let _ = Command::new("cynagectl").arg("-s").arg("dark").spawn();
-                        } else if now && !prefer_output {
-                            show_notification(&notiv_clone_for_wall, "wallpaper changed");
-                        } else if !now && !prefer_output {
-                            show_notification(&notiv_clone_for_wall, "wallpaper changed, Light wallpaper detected");
-                            let _ = Command::new("cynagectl").arg("-s").arg("light").spawn();
-                        } else {
-                            show_notification(&notiv_clone_for_wall, "wallpaper changed");
-                        }
-                    });
-                    boxxy.append(&btn);
-                }
-            }
-        }
-    };
+    wallpaper_box.append(&current_wall);
+    wallpaper_box.append(&wall_sources_switcher);
+    wallpaper_box.append(&wall_sources);
 
-    if let Some(current_pic_ref) = current_pic_ref_opt {
+    {
+        let current_pic_ref = WallState {
+            current_pic: Rc::new(RefCell::new(current_pic.clone())),
+            current_preview: current_preview.clone(),
+            current_name: current_wall_name.clone(),
+            auto_theme: auto_theme.clone(),
+            ignore_check: ignore_check.clone(),
+            select_toggle: select_wall.clone(),
+            selected: Rc::new(RefCell::new(Vec::new())),
+            meta: Rc::new(RefCell::new(load_pruned_wall_meta())),
+            stats_tried: Rc::new(RefCell::new(HashSet::new())),
+            scroller: scrolled_window.clone(),
+            strip: image_grid.clone(),
+            gallery: image_gallery.clone(),
+            search: wall_search.clone(),
+            sort: wall_sort.clone(),
+            favourites_toggle: wall_favourites.clone(),
+            wall_buttons: Rc::new(RefCell::new(Vec::new())),
+            gallery_toggle: wall_gallery.clone(),
+            video_output: video_output.clone(),
+        };
         let current_pic_ref2 = current_pic_ref.clone();
-        add_walls_to_grid(&image_grid, &notif_box, &current_pic_ref);
-        let image_grid_clone = image_grid.clone();
+        let current_pic_ref3 = current_pic_ref.clone();
+        let current_displayed = current_wall_path.as_ref().map(|p| displayed_wallpaper(&current_pic_ref, p));
+        show_current_wallpaper(&current_pic_ref, current_displayed.as_deref());
+        add_walls_to_grid(&notif_box, &current_pic_ref);
+        let color_page = build_color_wallpaper_page(&notif_box, &current_pic_ref);
+        wall_sources.add_titled(&color_page, Some("color"), "Color");
         let notif_box_clone = notif_box.clone();
         let window_clone = window.clone();
         add_wall.connect_clicked(move |_| {
@@ -1235,60 +7786,108 @@ fn build_ui(app: &Application) {
             dialog.set_size_request(400, 800);
             add_class_recursive(&dialog.upcast_ref(), "wall-dialog");
 
-            let image_grid_inner = image_grid_clone.clone();
+            dialog.set_select_multiple(true);
+
             dialog.connect_response(move |dialog, response| {
                 if response == ResponseType::Accept {
-                    if let Some(file_path) = dialog.file().and_then(|f| f.path()) {
-                        let _ = Command::new("cynagectl")
-                            .args(["-w", "add", file_path.to_str().unwrap_or_default()])
-                            .spawn();
-                    }
+                    let files = dialog.files();
+                    let paths: Vec<PathBuf> = (0..files.n_items())
+                        .filter_map(|i| files.item(i).and_downcast::<gtk4::gio::File>())
+                        .filter_map(|f| f.path())
+                        .collect();
+                    let notif_box_inner = notif_box_clone.clone();
+                    let curren_pic_ref_inner = curren_pic_ref_clone.clone();
+                    import_wallpapers(paths, &notif_box_clone, move || {
+                        add_walls_to_grid(&notif_box_inner, &curren_pic_ref_inner);
+                    });
                 }
                 dialog.close();
-                add_walls_to_grid(&image_grid_inner, &notif_box_clone, &curren_pic_ref_clone);
             });
             dialog.show();
         });
 
-        let window_clone2 = window.clone();
-        let image_grid_clone = image_grid.clone();
+        // first run: import something when the folder is empty, otherwise open the gallery to choose from
+        let notif_box_clone = notif_box.clone();
+        let add_wall_clone = add_wall.clone();
+        let wall_gallery_clone = wall_gallery.clone();
+        pick_wall.connect_clicked(move |_| {
+            let home_dir = env::var("HOME").unwrap_or_default();
+            let has_walls = fs::read_dir(format!("{}/.config/swww/cynage", home_dir))
+                .map(|entries| entries.flatten().any(|e| e.path().is_file()))
+                .unwrap_or(false);
+            if has_walls {
+                wall_gallery_clone.set_active(true);
+                show_notification(&notif_box_clone, "click a wallpaper to set it");
+            } else {
+                add_wall_clone.emit_clicked();
+            }
+        });
+
+        // select mode: clicking thumbnails toggles selection instead of setting the wallpaper
         let notif_box_clone2 = notif_box.clone();
-        let current_pic_ref2 = current_pic_ref2.clone();
-        remove_wall.connect_clicked(move |_| {
-            let notif_box_clone2 = notif_box_clone2.clone();
-            let curren_pic_ref_clone2 = current_pic_ref2.clone();
-            let dialog = FileChooserDialog::new(
-                Some("Select wallpaper to remove"),
-                Some(&window_clone2),
-                FileChooserAction::Open,
-                &[("_Cancel", ResponseType::Cancel), ("_Remove", ResponseType::Accept)],
-            );
-            dialog.set_css_classes(&["wall-dialog"]);
-            dialog.set_size_request(400, 800);
-            add_class_recursive(&dialog.upcast_ref(), "wall-dialog");
+        let current_pic_ref_select = current_pic_ref2.clone();
+        let remove_wall_clone = remove_wall.clone();
+        let trash_wall_clone = trash_wall.clone();
+        select_wall.connect_toggled(move |toggle| {
+            remove_wall_clone.set_visible(toggle.is_active());
+            trash_wall_clone.set_visible(toggle.is_active());
+            // redraw to drop any selection highlight
+            add_walls_to_grid(&notif_box_clone2, &current_pic_ref_select);
+        });
+
+        for (button, removal) in [(&remove_wall, WallRemoval::Remove), (&trash_wall, WallRemoval::Trash)] {
+            let notif_box_clone2 = notif_box.clone();
+            let current_pic_ref2 = current_pic_ref2.clone();
+            button.connect_clicked(move |_| {
+                let selected = current_pic_ref2.selected.borrow().clone();
+                confirm_wallpaper_removal(selected, removal, &notif_box_clone2, &current_pic_ref2);
+            });
+        }
 
-            let image_grid_inner = image_grid_clone.clone();
-            if let Some(home_dir) = std::env::var_os("HOME") {
-                let start_path = Path::new(&home_dir).join(".config/swww/cynage/");
-                let _ = dialog.set_current_folder(Some(&gtk4::gio::File::for_path(start_path)));
+        let refresh_walls = {
+            let notif_box = notif_box.clone();
+            let wall_state = current_pic_ref2.clone();
+            move || add_walls_to_grid(&notif_box, &wall_state)
+        };
+        let wall_state_filter = current_pic_ref2.clone();
+        wall_search.connect_search_changed(move |_| filter_walls(&wall_state_filter));
+        let refresh = refresh_walls.clone();
+        wall_sort.connect_selected_notify(move |_| refresh());
+        let wall_state_filter = current_pic_ref2.clone();
+        wall_favourites.connect_toggled(move |_| filter_walls(&wall_state_filter));
+
+        // the strip scrolls sideways under the current wallpaper, the gallery wraps and takes the page
+        let current_pic_ref4 = current_pic_ref2.clone();
+        wall_gallery.connect_toggled(move |toggle| {
+            let scroller = &current_pic_ref4.scroller;
+            if toggle.is_active() {
+                scroller.set_child(Some(&current_pic_ref4.gallery));
+                scroller.set_policy(gtk4::PolicyType::Never, gtk4::PolicyType::Automatic);
+                scroller.set_vexpand(true);
+            } else {
+                scroller.set_child(Some(&current_pic_ref4.strip));
+                scroller.set_policy(gtk4::PolicyType::Automatic, gtk4::PolicyType::Never);
+                scroller.set_vexpand(false);
             }
+            refresh_walls();
+        });
 
-            dialog.connect_response(move |dialog, response| {
-                if response == ResponseType::Accept {
-                    if let Some(file_path) = dialog.file().and_then(|f| f.path()) {
-                        if let Some(file_stem) = file_path.file_stem().and_then(|s| s.to_str()) {
-                            let _ = Command::new("cynagectl")
-                                .args(["-w", "remove", file_stem])
-                                .spawn();
-                        }
-                    }
-                }
-                dialog.close();
-                add_walls_to_grid(&image_grid_inner, &notif_box_clone2, &curren_pic_ref_clone2);
+        // drag files or folders from a file manager onto the strip
+        let drop_target = gtk4::DropTarget::new(gdk::FileList::static_type(), gdk::DragAction::COPY);
+        let notif_box_clone3 = notif_box.clone();
+        drop_target.connect_drop(move |_, value, _, _| {
+            let Ok(file_list) = value.get::<gdk::FileList>() else {
+                return false;
+            };
+            let paths: Vec<PathBuf> = file_list.files().iter().filter_map(|f| f.path()).collect();
+            let notif_box_inner = notif_box_clone3.clone();
+            let current_pic_ref_inner = current_pic_ref3.clone();
+            import_wallpapers(paths, &notif_box_clone3, move || {
+                add_walls_to_grid(&notif_box_inner, &current_pic_ref_inner);
             });
-
-            dialog.show();
+            true
         });
+        scrolled_window.add_controller(drop_target);
 
     }
    
@@ -1412,6 +8011,19 @@ BBBBB++BBBBB+++++++++BBBBBB
 BBBBB++BBBBB+++++++++BBBBBB
 BBBBB++++++++++++++++BBBBBB", "startup", "Shell configs >> Startup sound settings");
 
+    add_shell_button("      ██      
+    ██████    
+  ██████████  
+  ██████████  
+  ██████████  
+██████████████
+      ██      ", "events", "Shell configs >> Event sound settings");
+
+    add_shell_button("▶ ════════
+▶ ══════
+▶ ═════════
+▶ ════", "autostart", "Shell configs >> Autostart applications");
+
 
     let shell_stack_clone_back: Stack = shell_stack.clone();
     let page_title_clone = page_title.clone();
@@ -1519,63 +8131,139 @@ BBBBB++++++++++++++++BBBBBB", "startup", "Shell configs >> Startup sound setting
     let config_path = format!("{}/.config/hypr/startup.conf", home_dir);
     let sound_dir = PathBuf::from(format!("{}/.config/hypr/sound/startup", home_dir));
 
-    let selected_button: Rc<RefCell<Option<Button>>> = Rc::new(RefCell::new(None));
-
-    if let Ok(entries) = fs::read_dir(&sound_dir) {
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if path.is_file() {
-                let filename = path.file_name().unwrap().to_string_lossy().to_string();
-
-                let btn = Button::with_label(&filename);
-                btn.set_css_classes(&["sound_btn"]);
-                btn.set_size_request(100, 100);
-                btn.set_valign(gtk4::Align::Center);
-                btn.set_halign(gtk4::Align::Center);
-
-                // Initial highlight if currently selected
-                if let Some(current_file) = extract_current_selection(&config_path) {
-                    if current_file == filename {
-                        btn.add_css_class("sound_btn_selected");
-                        *selected_button.borrow_mut() = Some(btn.clone());
-                    }
-                }
-
-                let selected_button_clone = selected_button.clone();
-                let config_path_clone = config_path.clone();
-                let filename_clone = filename.clone();
-                let notiv_box_clone_sound = notif_box.clone();
-
-                btn.connect_clicked(move |btn_ref| {
-                    // Deselect previous button
-                    if let Some(prev_btn) = selected_button_clone.borrow_mut().take() {
-                        prev_btn.remove_css_class("sound_btn_selected");
-                    }
-
-                    // Select current
-                    btn_ref.add_css_class("sound_btn_selected");
-                    *selected_button_clone.borrow_mut() = Some(btn_ref.clone());
+    let startup_page = StartupSoundPage {
+        config_path: config_path.clone(),
+        current: Rc::new(RefCell::new(extract_current_selection(&config_path).unwrap_or_default())),
+        selected_button: Rc::new(RefCell::new(None)),
+        preview: Rc::new(RefCell::new(None)),
+        button_box: sound_button_box.clone(),
+        notif_box: notif_box.clone(),
+    };
+    let sound_preview = startup_page.preview.clone();
+
+    // player and volume for the exec-once line
+    let sound_options = GtkBox::new(Orientation::Horizontal, 10);
+    let mut players = available_sound_players();
+    let current_player = startup_page.current.borrow().player.clone();
+    if let Some(known) = SOUND_PLAYERS.iter().find(|p| **p == current_player) {
+        if !players.contains(known) {
+            players.push(known);
+        }
+    }
+    if players.is_empty() {
+        players.push("mpv");
+        show_notification(&notif_box, "no sound player found, install mpv, pw-play or paplay");
+    }
+    let player_drop = gtk4::DropDown::from_strings(&players);
+    if let Some(index) = players.iter().position(|p| *p == current_player) {
+        player_drop.set_selected(index as u32);
+    }
+    let volume_scale = gtk4::Scale::with_range(Orientation::Horizontal, 0.0, 100.0, 5.0);
+    volume_scale.set_value(startup_page.current.borrow().volume as f64);
+    volume_scale.set_draw_value(true);
+    volume_scale.set_size_request(200, -1);
+    sound_options.append(&Label::new(Some("Player")));
+    sound_options.append(&player_drop);
+    sound_options.append(&Label::new(Some("Volume")));
+    sound_options.append(&volume_scale);
+    startup_box.append(&sound_options);
+
+    let startup_page_clone = startup_page.clone();
+    player_drop.connect_selected_notify(move |drop| {
+        if let Some(player) = players.get(drop.selected() as usize) {
+            startup_page_clone.current.borrow_mut().player = player.to_string();
+            startup_page_clone.save();
+        }
+    });
+    // dragging fires on every step, startup.conf is written once the slider rests
+    let startup_page_clone = startup_page.clone();
+    let pending_save: Rc<RefCell<Option<glib::SourceId>>> = Rc::new(RefCell::new(None));
+    volume_scale.connect_value_changed(move |scale| {
+        startup_page_clone.current.borrow_mut().volume = scale.value() as u32;
+        if let Some(source) = pending_save.borrow_mut().take() {
+            source.remove();
+        }
+        let startup_page = startup_page_clone.clone();
+        let pending = pending_save.clone();
+        *pending_save.borrow_mut() = Some(glib::timeout_add_local_once(std::time::Duration::from_millis(300), move || {
+            pending.borrow_mut().take();
+            startup_page.save();
+        }));
+    });
 
-                    // Write config
-                    let conf_content = format!(
-                        "exec-once = mpv --no-video --volume=100 \"$HOME/.config/hypr/sound/startup/{}\"\n",
-                        filename_clone
-                    );
-                    if let Ok(mut file) = OpenOptions::new()
-                        .write(true)
-                        .truncate(true)
-                        .create(true)
-                        .open(&config_path_clone)
-                    {
-                        let _ = file.write_all(conf_content.as_bytes());
-                    }
+    // import, optionally trimmed and loudness normalised
+    let import_options = GtkBox::new(Orientation::Horizontal, 10);
+    let import_sound = Button::with_label("Import sounds");
+    let trim_check = gtk4::CheckButton::with_label("Trim to");
+    let trim_seconds = gtk4::SpinButton::with_range(1.0, 60.0, 1.0);
+    trim_seconds.set_value(10.0);
+    let normalise_check = gtk4::CheckButton::with_label("Normalise loudness");
+    import_options.append(&import_sound);
+    import_options.append(&trim_check);
+    import_options.append(&trim_seconds);
+    import_options.append(&Label::new(Some("seconds")));
+    import_options.append(&normalise_check);
+    startup_box.append(&import_options);
+
+    let current_import_options = move || SoundImportOptions {
+        max_length: trim_check.is_active().then(|| trim_seconds.value()),
+        normalise: normalise_check.is_active(),
+    };
 
-                    show_notification(&notiv_box_clone_sound, "Startup Sound Modified");
+    let startup_page_clone = startup_page.clone();
+    let current_import_options_clone = current_import_options.clone();
+    let window_clone = window.clone();
+    import_sound.connect_clicked(move |_| {
+        let dialog = FileChooserDialog::new(
+            Some("Select sounds to import"),
+            Some(&window_clone),
+            FileChooserAction::Open,
+            &[("_Cancel", ResponseType::Cancel), ("_Import", ResponseType::Accept)],
+        );
+        dialog.set_css_classes(&["wall-dialog"]);
+        dialog.set_size_request(400, 800);
+        add_class_recursive(dialog.upcast_ref(), "wall-dialog");
+        dialog.set_select_multiple(true);
+        let audio_filter = gtk4::FileFilter::new();
+        audio_filter.set_name(Some("Audio"));
+        audio_filter.add_mime_type("audio/*");
+        dialog.add_filter(&audio_filter);
+
+        let startup_page_inner = startup_page_clone.clone();
+        let options = current_import_options_clone();
+        dialog.connect_response(move |dialog, response| {
+            if response == ResponseType::Accept {
+                let files = dialog.files();
+                let paths: Vec<PathBuf> = (0..files.n_items())
+                    .filter_map(|i| files.item(i).and_downcast::<gtk4::gio::File>())
+                    .filter_map(|f| f.path())
+                    .collect();
+                import_startup_sounds(paths, options, &startup_page_inner);
+            }
+            dialog.close();
+        });
+        dialog.show();
+    });
 
-                });
+    // drag audio files from a file manager onto the page
+    let drop_target = gtk4::DropTarget::new(gdk::FileList::static_type(), gdk::DragAction::COPY);
+    let startup_page_clone = startup_page.clone();
+    drop_target.connect_drop(move |_, value, _, _| {
+        let Ok(file_list) = value.get::<gdk::FileList>() else {
+            return false;
+        };
+        let paths: Vec<PathBuf> = file_list.files().iter().filter_map(|f| f.path()).collect();
+        import_startup_sounds(paths, current_import_options(), &startup_page_clone);
+        true
+    });
+    startup_box.add_controller(drop_target);
 
-                sound_button_box.append(&btn);
-            }
+    add_startup_sound_button(&startup_page, None);
+    if let Ok(entries) = fs::read_dir(&sound_dir) {
+        let mut paths: Vec<PathBuf> = entries.flatten().map(|e| e.path()).filter(|p| p.is_file()).collect();
+        paths.sort();
+        for path in paths {
+            add_startup_sound_button(&startup_page, Some(&path));
         }
     }
 
@@ -1583,6 +8271,22 @@ BBBBB++++++++++++++++BBBBBB", "startup", "Shell configs >> Startup sound setting
     shell_stack.add_titled(&switch_box, Some("switches"), "Misc");
     shell_stack.add_titled(&startup_box, Some("startup"), "startup_sound");
 
+    let (event_sounds_box, event_sound_preview) = build_event_sounds_page(&notif_box, &window);
+    let event_sounds_scroller = ScrolledWindow::builder().child(&event_sounds_box).vexpand(true).build();
+    shell_stack.add_titled(&event_sounds_scroller, Some("events"), "event_sounds");
+    shell_stack.add_titled(&build_autostart_page(&notif_box, &window), Some("autostart"), "autostart");
+
+    // don't keep a preview playing after leaving the page
+    let sound_preview_clone = sound_preview.clone();
+    shell_stack.connect_visible_child_name_notify(move |stack| {
+        if stack.visible_child_name().as_deref() != Some("startup") {
+            stop_sound_preview(&sound_preview_clone);
+        }
+        if stack.visible_child_name().as_deref() != Some("events") {
+            stop_sound_preview(&event_sound_preview);
+        }
+    });
+
     stack.add_titled(&shell_stack, Some("cynide"), "Cynide Settings");
 
     // network settings ------------------------------------------------------------------------------------------------------------------------------- //
@@ -1596,12 +8300,29 @@ BBBBB++++++++++++++++BBBBBB", "startup", "Shell configs >> Startup sound setting
     let nm_ctrl = GtkBox::new(Orientation::Horizontal, 7);
     nm_ctrl.set_hexpand(true);
 
+    // shared connection for the list, connecting and the secret agent
+    let nm_conn = nm_connection();
+
     let nm_toggle = Switch::new();
-    nm_toggle.set_active(getty_wifi_status());
+    nm_toggle.set_active(
+        nm_conn
+            .as_ref()
+            .and_then(|nm| nm_prop::<bool>(nm, NM_PATH, NM_IFACE, "WirelessEnabled").ok())
+            .unwrap_or(false),
+    );
     nm_toggle.set_halign(gtk4::Align::End);
 
-    nm_toggle.connect_state_set(|_, state| {
-        setty_wifi_enabled(state);
+    // set while a WirelessEnabled change from NetworkManager moves the switch
+    let radio_updating = Rc::new(std::cell::Cell::new(false));
+    let radio_updating_clone = radio_updating.clone();
+    let nm_conn_clone = nm_conn.clone();
+    nm_toggle.connect_state_set(move |_, state| {
+        if radio_updating_clone.get() {
+            return glib::Propagation::Proceed;
+        }
+        if let Some(Err(e)) = nm_conn_clone.as_ref().map(|nm| set_wifi_enabled(nm, state)) {
+            eprintln!("could not switch the Wi-Fi radio: {}", e);
+        }
         glib::Propagation::Proceed
     });
 
@@ -1609,7 +8330,15 @@ BBBBB++++++++++++++++BBBBBB", "startup", "Shell configs >> Startup sound setting
     nm_edit_button.set_halign(gtk4::Align::Start);
     nm_edit_button.set_hexpand(true);
 
+    let nm_join_button = Button::with_label("Join Other Network…");
+    nm_join_button.set_halign(gtk4::Align::Start);
+
+    let nm_qr_button = Button::with_label("Join From QR Image…");
+    nm_qr_button.set_halign(gtk4::Align::Start);
+
     nm_ctrl.append(&nm_edit_button);
+    nm_ctrl.append(&nm_join_button);
+    nm_ctrl.append(&nm_qr_button);
     nm_ctrl.append(&nm_toggle);
 
     let nm_list_scroller = ScrolledWindow::builder()
@@ -1620,80 +8349,95 @@ BBBBB++++++++++++++++BBBBBB", "startup", "Shell configs >> Startup sound setting
         .halign(gtk4::Align::Fill)
         .valign(gtk4::Align::Fill)
         .build();
-    let network_list = GtkBox::new(Orientation::Vertical, 10);
-    nm_list_scroller.set_child(Some(&network_list));
+    let wifi_page = nm_conn.as_ref().map(|nm| WifiPage::new(nm, &notif_box));
+    match &wifi_page {
+        Some(wifi_page) => nm_list_scroller.set_child(Some(&build_wifi_list(wifi_page))),
+        None => nm_list_scroller.set_child(Some(&Label::new(Some("NetworkManager is not reachable on the system bus")))),
+    }
     nm_list_scroller.set_css_classes(&["display_win", "border_cell"]);
 
-    network_home.append(&nm_ctrl);
-    network_home.append(&nm_list_scroller);
-
-
-    let vte_box = GtkBox::new(Orientation::Vertical, 0);
-    vte_box.set_hexpand(true);
-    vte_box.set_vexpand(true);
-    vte_box.set_halign(gtk4::Align::Fill);
-    vte_box.set_valign(gtk4::Align::Fill);
-    vte_box.set_css_classes(&["termbox"]);
-
-    let vte_term = Terminal::default();
-    vte_term.set_vexpand(true);
-    vte_term.set_hexpand(true);
-    let fg = gtk4::gdk::RGBA::new(0.0, 1.0, 0.66, 1.0);
-    let bg = gtk4::gdk::RGBA::new(0.0, 0.0, 0.0, 1.0);
-
-    let palette_owned: Vec<gtk4::gdk::RGBA> = vec![
-        gtk4::gdk::RGBA::new(0.00, 0.40, 0.27, 1.0),
-        gtk4::gdk::RGBA::new(0.00, 0.49, 0.32, 1.0),
-        gtk4::gdk::RGBA::new(0.00, 0.57, 0.38, 1.0),
-        gtk4::gdk::RGBA::new(0.00, 0.66, 0.44, 1.0),
-        gtk4::gdk::RGBA::new(0.00, 0.74, 0.50, 1.0),
-        gtk4::gdk::RGBA::new(0.00, 0.83, 0.55, 1.0),
-        gtk4::gdk::RGBA::new(0.00, 0.91, 0.61, 1.0),
-        gtk4::gdk::RGBA::new(0.00, 1.00, 0.67, 1.0),
-    ];
-    let palette: Vec<&gtk4::gdk::RGBA> = palette_owned.iter().collect();
-    vte_term.set_colors(Some(&fg), Some(&bg), &palette);
-
-    vte_box.append(&vte_term);
-    net_stack.add_titled(&network_home, Some("home"), "Network Home");
-    net_stack.add_titled(&vte_box, Some("edit"), "Edit Saved");
+    let connections_page = nm_conn.as_ref().map(|nm| build_connections_page(nm, &notif_box));
 
+    // the edit button and a wired device's Profile button both land on the editor
     let back_button_edit = back_button.clone();
-
     let stack_weak = net_stack.downgrade();
     let page_title_clone_edit = page_title.clone();
-
-    
-    nm_edit_button.connect_clicked(move |_| {
-        vte_term.spawn_async(
-            PtyFlags::DEFAULT,
-            None,      
-            &["nmtui", "edit"],          
-            &[],            
-            gtk4::glib::SpawnFlags::DEFAULT,
-            || {},               
-            -1,                      
-            None::<&gtk4::gio::Cancellable>,  
-            move |res: Result<gtk4::glib::Pid, gtk4::glib::Error>| {
-                if let Err(e) = res {
-                    eprintln!("Failed to spawn terminal process: {}", e);
-                }
-            },
-        );
-        if let Some(stack) = stack_weak.upgrade() {    
+    let connections_page_clone = connections_page.as_ref().map(|(_, page)| page.clone());
+    let show_connections = Rc::new(move |profile: Option<dbus::Path<'static>>| {
+        if let Some(page) = &connections_page_clone {
+            match profile {
+                Some(path) => page.show_profile(path),
+                None => page.reload(),
+            }
+        }
+        if let Some(stack) = stack_weak.upgrade() {
             stack.set_visible_child_name("edit");
-            typing_effect(&page_title_clone_edit, "network settings >> edit saved connections (use keyboard)", 10);
+            typing_effect(&page_title_clone_edit, "network settings >> edit saved connections", 10);
             back_button_edit.set_visible(true);
         }
     });
 
-    // Timer for 1s refresh
-    let network_list_clone = network_list.clone();
-    glib::timeout_add_seconds_local(1, move || {
-        let conn = dbus::blocking::Connection::new_system().unwrap();
-        refresh_wifi_listty(&conn, &network_list_clone);
-        glib::ControlFlow::Continue
-    });
+    let show_connections_clone = show_connections.clone();
+    let wired_section = nm_conn
+        .as_ref()
+        .map(|nm| WiredSection::new(nm, &notif_box, move |path| show_connections_clone(Some(path))));
+
+    let vpn_section = nm_conn.as_ref().map(|nm| VpnSection::new(nm, &notif_box, &window));
+
+    network_home.append(&nm_ctrl);
+    if let Some(wired_section) = &wired_section {
+        network_home.append(&wired_section.section);
+    }
+    if let Some(vpn_section) = &vpn_section {
+        network_home.append(&vpn_section.section);
+    }
+    let hotspot = wifi_page.as_ref().map(|page| Hotspot::new(page, &window));
+    if let Some(hotspot) = &hotspot {
+        network_home.append(&hotspot.section);
+    }
+    network_home.append(&nm_list_scroller);
+
+
+    net_stack.add_titled(&network_home, Some("home"), "Network Home");
+    match &connections_page {
+        Some((page_box, _)) => net_stack.add_titled(page_box, Some("edit"), "Edit Saved"),
+        None => net_stack.add_titled(&Label::new(Some("NetworkManager is not reachable on the system bus")), Some("edit"), "Edit Saved"),
+    };
+
+    nm_edit_button.connect_clicked(move |_| show_connections(None));
+
+    match &wifi_page {
+        Some(wifi_page) => {
+            let (wifi_page, window) = (wifi_page.clone(), window.clone());
+            let (wifi_page_clone, window_clone) = (wifi_page.clone(), window.clone());
+            nm_join_button.connect_clicked(move |_| {
+                open_join_network_dialog(&wifi_page_clone, Some(window_clone.upcast_ref()), None, WifiSecurity::Psk);
+            });
+            nm_qr_button.connect_clicked(move |_| join_from_qr_image(&wifi_page, &window));
+        }
+        None => {
+            nm_join_button.set_sensitive(false);
+            nm_qr_button.set_sensitive(false);
+        }
+    }
+
+    if let (Some(nm_conn), Some(wifi_page)) = (&nm_conn, &wifi_page) {
+        register_secret_agent(nm_conn, &window);
+        wifi_page.reload();
+        watch_wifi_signals(wifi_page);
+    }
+    if let Some(wired_section) = &wired_section {
+        wired_section.reload();
+        watch_wired_signals(wired_section);
+    }
+    if let Some(vpn_section) = &vpn_section {
+        vpn_section.reload();
+        watch_vpn_signals(vpn_section);
+    }
+    if let Some(hotspot) = &hotspot {
+        hotspot.reload();
+        watch_hotspot_signals(hotspot, &nm_toggle, &radio_updating);
+    }
 
 
     stack.add_titled(&net_stack, Some("network"), "Network Settings");
@@ -1734,9 +8478,181 @@ fn main() {
     let app = Application::builder()
         .application_id("ekah.scu.calibrate")
         .build();
+    app.connect_startup(|_| {
+        gtk4::gio::spawn_blocking(flush_removed_wallpapers);
+    });
     app.connect_activate(|app| {
         load_css();
         build_ui(app);
     });
+    // removals still waiting on their undo toast
+    app.connect_shutdown(|_| flush_removed_wallpapers());
     app.run();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gtk4::gdk_pixbuf::{Colorspace, Pixbuf};
+
+    const SIZE: i32 = 64;
+
+    /// An RGB pixbuf whose grey level at (x, y) is `grey(x, y)`.
+    fn grey_pixbuf(grey: impl Fn(i32, i32) -> u8) -> Pixbuf {
+        let mut data = Vec::with_capacity((SIZE * SIZE * 3) as usize);
+        for y in 0..SIZE {
+            for x in 0..SIZE {
+                let g = grey(x, y);
+                data.extend_from_slice(&[g, g, g]);
+            }
+        }
+        Pixbuf::from_bytes(&glib::Bytes::from_owned(data), Colorspace::Rgb, false, 8, SIZE, SIZE, SIZE * 3)
+    }
+
+    fn config(threshold: f64, regions: Vec<WeightedRegion>) -> AutoThemeConfig {
+        AutoThemeConfig { threshold, regions, ignored: Vec::new() }
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 0.5, "lightness {} expected {}", actual, expected);
+    }
+
+    #[test]
+    fn solid_pixbufs() {
+        assert_close(pixbuf_lightness(&grey_pixbuf(|_, _| 0), &[]).unwrap(), 0.0);
+        assert_close(pixbuf_lightness(&grey_pixbuf(|_, _| 255), &[]).unwrap(), 100.0);
+        // sRGB 119 is the perceptual middle grey
+        assert_close(pixbuf_lightness(&grey_pixbuf(|_, _| 119), &[]).unwrap(), 50.0);
+    }
+
+    #[test]
+    fn averages_in_linear_light() {
+        // half black, half white averages to 0.5 linear, L* 76, not the 50 of an sRGB average
+        let split = grey_pixbuf(|x, _| if x < SIZE / 2 { 0 } else { 255 });
+        assert_close(pixbuf_lightness(&split, &[]).unwrap(), linear_to_lightness(0.5));
+        assert_close(linear_to_lightness(0.5), 76.07);
+
+        let gradient = grey_pixbuf(|x, _| (x * 255 / (SIZE - 1)) as u8);
+        let mean = (0..SIZE).map(|x| srgb_to_linear((x * 255 / (SIZE - 1)) as u8)).sum::<f64>() / SIZE as f64;
+        assert_close(pixbuf_lightness(&gradient, &[]).unwrap(), linear_to_lightness(mean));
+    }
+
+    #[test]
+    fn regions_are_weighted() {
+        let top_white = grey_pixbuf(|_, y| if y < SIZE / 2 { 255 } else { 0 });
+        assert_close(pixbuf_lightness(&top_white, &[]).unwrap(), linear_to_lightness(0.5));
+        let top_heavy = [WeightedRegion { x: 0.0, y: 0.0, w: 1.0, h: 0.5, weight: 3.0 }];
+        assert_close(pixbuf_lightness(&top_white, &top_heavy).unwrap(), linear_to_lightness(0.75));
+        let bottom_heavy = [WeightedRegion { x: 0.0, y: 0.5, w: 1.0, h: 0.5, weight: 3.0 }];
+        assert_close(pixbuf_lightness(&top_white, &bottom_heavy).unwrap(), linear_to_lightness(0.25));
+    }
+
+    #[test]
+    fn threshold_decides_dark() {
+        let defaults = config(50.0, AutoThemeConfig::default_regions());
+        assert_eq!(pixbuf_is_dark(&grey_pixbuf(|_, _| 0), &defaults), Some(true));
+        assert_eq!(pixbuf_is_dark(&grey_pixbuf(|_, _| 255), &defaults), Some(false));
+        // on the threshold counts as dark
+        let middle = grey_pixbuf(|_, _| 119);
+        let lightness = pixbuf_lightness(&middle, &[]).unwrap();
+        assert_eq!(pixbuf_is_dark(&middle, &config(lightness, Vec::new())), Some(true));
+        assert_eq!(pixbuf_is_dark(&middle, &config(lightness - 1.0, Vec::new())), Some(false));
+    }
+
+    fn set_sound_line(content: &str, line: Option<&str>) -> String {
+        set_managed_line(content, STARTUP_SOUND_MARKER, line, |l| parse_startup_sound_line(l).is_some())
+    }
+
+    #[test]
+    fn managed_line_table() {
+        const OLD: &str = "exec-once = mpv --no-video --volume=50 \"$HOME/.config/hypr/sound/startup/old.ogg\"";
+        const NEW: &str = "exec-once = pw-play --volume=0.80 \"$HOME/.config/hypr/sound/startup/new.ogg\"";
+        let tagged_old = format!("{} {}", OLD, STARTUP_SOUND_MARKER);
+        let tagged_new = format!("{} {}", NEW, STARTUP_SOUND_MARKER);
+
+        // (case, content, line, expected)
+        let cases: Vec<(&str, String, Option<&str>, String)> = vec![
+            ("empty file", String::new(), Some(NEW), format!("{}\n", tagged_new)),
+            ("empty file, sound off", String::new(), None, String::new()),
+            (
+                "tagged line",
+                format!("exec-once = waybar\n{}\nexec-once = dunst\n", tagged_old),
+                Some(NEW),
+                format!("exec-once = waybar\n{}\nexec-once = dunst\n", tagged_new),
+            ),
+            (
+                "tagged line removed",
+                format!("exec-once = waybar\n{}\nexec-once = dunst\n", tagged_old),
+                None,
+                "exec-once = waybar\nexec-once = dunst\n".to_string(),
+            ),
+            ("legacy line", format!("{}\nexec-once = waybar\n", OLD), Some(NEW), format!("{}\nexec-once = waybar\n", tagged_new)),
+            (
+                "tagged line wins over legacy",
+                format!("{}\n{}\n", OLD, tagged_old),
+                Some(NEW),
+                format!("{}\n{}\n", OLD, tagged_new),
+            ),
+            (
+                "duplicate tagged lines",
+                format!("{}\nexec-once = waybar\n{}\n", tagged_old, tagged_old),
+                Some(NEW),
+                format!("{}\nexec-once = waybar\n", tagged_new),
+            ),
+            (
+                "commented-out tagged line",
+                format!("exec-once = waybar\n# {}\n", tagged_old),
+                Some(NEW),
+                format!("exec-once = waybar\n# {}\n", tagged_new),
+            ),
+            (
+                "disabled on the Autostart page",
+                format!("{} {}\n", AUTOSTART_DISABLED_MARKER, tagged_old),
+                Some(NEW),
+                format!("{} {}\n", AUTOSTART_DISABLED_MARKER, tagged_new),
+            ),
+            (
+                "other exec-once lines",
+                "exec-once = waybar\nexec-once = mpv ~/music.ogg\n".to_string(),
+                Some(NEW),
+                format!("exec-once = waybar\nexec-once = mpv ~/music.ogg\n{}\n", tagged_new),
+            ),
+            ("no trailing newline", format!("exec-once = waybar\n{}", tagged_old), Some(NEW), format!("exec-once = waybar\n{}", tagged_new)),
+            ("no trailing newline, appended", "exec-once = waybar".to_string(), Some(NEW), format!("exec-once = waybar\n{}\n", tagged_new)),
+        ];
+        for (case, content, line, expected) in cases {
+            assert_eq!(set_sound_line(&content, line), expected, "{}", case);
+        }
+    }
+
+    #[test]
+    fn startup_sound_round_trip() {
+        let sound = |player: &str, volume: u32| StartupSound { file: Some("chime.ogg".to_string()), player: player.to_string(), volume };
+
+        // (sound, expected line)
+        let cases = [
+            (sound("mpv", 50), Some("exec-once = mpv --no-video --volume=50 \"$HOME/.config/hypr/sound/startup/chime.ogg\"")),
+            (sound("pw-play", 80), Some("exec-once = pw-play --volume=0.80 \"$HOME/.config/hypr/sound/startup/chime.ogg\"")),
+            (sound("paplay", 35), Some("exec-once = paplay --volume=22937 \"$HOME/.config/hypr/sound/startup/chime.ogg\"")),
+            (StartupSound { file: None, ..sound("mpv", 50) }, None),
+        ];
+        let config_path = std::env::temp_dir().join(format!("calibrate-startup-sound-{}.conf", std::process::id()));
+        for (sound, expected) in cases {
+            let line = startup_sound_line(&sound);
+            assert_eq!(line.as_deref(), expected, "{}", sound.player);
+            let Some(line) = line else {
+                continue;
+            };
+            assert_eq!(parse_startup_sound_line(&line), Some(sound.clone()), "{}", sound.player);
+
+            // a legacy line comes first, the tagged one (disabled on the Autostart page) still wins
+            let content = format!(
+                "exec-once = mpv \"$HOME/.config/hypr/sound/startup/old.ogg\"\n{} {} {}\n",
+                AUTOSTART_DISABLED_MARKER, line, STARTUP_SOUND_MARKER
+            );
+            fs::write(&config_path, content).unwrap();
+            assert_eq!(extract_current_selection(&config_path.to_string_lossy()), Some(sound.clone()), "{}", sound.player);
+        }
+        let _ = fs::remove_file(&config_path);
+    }
+}